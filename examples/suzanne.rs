@@ -1,4 +1,4 @@
-use larnt::{load_obj, Matrix, Scene, TransformedShape, Vector};
+use larnt::{load_obj, Matrix, MeshEdges, Scene, TransformedShape, Vector};
 use std::sync::Arc;
 
 fn main() {
@@ -6,12 +6,19 @@ fn main() {
     let mut mesh = load_obj("examples/suzanne.obj").expect("Failed to load OBJ");
     mesh.unit_cube();
 
-    let transform = Matrix::rotate(Vector::new(0.0, 1.0, 0.0), 0.5);
-    scene.add_arc(Arc::new(TransformedShape::new(Arc::new(mesh), transform)));
-
     let eye = Vector::new(-0.5, 0.5, 2.0);
     let center = Vector::new(0.0, 0.0, 0.0);
     let up = Vector::new(0.0, 1.0, 0.0);
+
+    // Draw creases and the silhouette instead of every triangle edge.
+    // The silhouette is computed in mesh coordinates.
+    let transform = Matrix::rotate(Vector::new(0.0, 1.0, 0.0), 0.5);
+    let mesh = mesh.with_edges(MeshEdges::Feature {
+        crease_angle: 30.0,
+        eye: Some(transform.inverse().mul_position(eye)),
+    });
+    scene.add_arc(Arc::new(TransformedShape::new(Arc::new(mesh), transform)));
+
     let width = 1024.0;
     let height = 1024.0;
 
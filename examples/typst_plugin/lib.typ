@@ -363,6 +363,39 @@
   )
 }
 
+/// The union operation for shapes.
+/// Can be used to fuse multiple shapes into one, removing the lines that fall inside the combined volume.
+///
+/// ```example
+/// #render(
+///   eye: (3., 2., 2.),
+///   center: (0., 0., 0.5),
+///   step: 0.05,
+///   fovy: 30.,
+///   union(
+///     cube((-0.5, -0.5, 0.), (.5, 0.5, 1.0), texture: "Stripes", stripes: 16),
+///     sphere((0., 0., 1.), 0.4),
+///   ),
+/// )
+/// ```
+///
+/// -> shape
+#let union(
+  /// The shapes to be combined.
+  /// At least two shapes are required.
+  /// -> shape
+  ..shapes,
+) = {
+  let shapes = shapes.pos()
+  assert(
+    shapes.len() >= 2 and shapes.all(s => type(s) == dictionary),
+    message: "union(...) expects two or more shape arguments",
+  )
+  return (
+    Union: shapes,
+  )
+}
+
 /// The difference operation for shapes.
 /// Can be used to create complex shapes by subtracting multiple shapes from a base shape.
 ///
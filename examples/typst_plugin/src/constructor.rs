@@ -57,6 +57,7 @@ pub enum LnShape {
     Mesh(Vec<LnShape>),
 
     Outline(Box<LnShape>), // Cone, Cylinder, Sphere
+    Union(Vec<LnShape>),
     Difference(Vec<LnShape>),
     Intersection(Vec<LnShape>),
     Transformation {
@@ -238,6 +239,13 @@ impl LnShape {
                     );
                 }
             },
+            LnShape::Union(ln_shapes) => {
+                let shapes = ln_shapes
+                    .into_iter()
+                    .map(|s| s.to_shape(eye, up))
+                    .collect::<Result<Vec<_>, _>>()?;
+                larnt::new_union(shapes)
+            }
             LnShape::Difference(ln_shapes) => {
                 let shapes = ln_shapes
                     .into_iter()
@@ -253,7 +261,7 @@ impl LnShape {
                 larnt::new_intersection(shapes)
             }
             LnShape::Transformation { shape, matrix } => Arc::new(larnt::TransformedShape::new(
-                shape.to_shape(matrix.clone().to_matrix().inverse().mul_position(eye), up)?,
+                shape.to_shape(eye, up)?,
                 matrix.to_matrix(),
             )),
         })
@@ -0,0 +1,268 @@
+//! Camera definition.
+//!
+//! This module provides the [`Camera`] struct, which bundles the view
+//! (eye, center, up), the projection and the viewport into a single value
+//! that can be passed to [`Scene::render_camera`](crate::Scene::render_camera).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Camera, Cube, RenderOptions, Scene, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+//!
+//! let camera = Camera::perspective(
+//!     Vector::new(4.0, 3.0, 2.0),
+//!     Vector::new(0.0, 0.0, 0.0),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     50.0,
+//! )
+//! .with_viewport(1024.0, 1024.0)
+//! .with_clip(0.1, 10.0);
+//!
+//! let paths = scene.render_camera(&camera, &RenderOptions::default());
+//! paths.write_to_svg("output.svg", camera.width, camera.height).unwrap();
+//! ```
+
+use crate::matrix::Matrix;
+use crate::vector::Vector;
+
+/// The viewpoint a shape is drawn from.
+///
+/// This is passed to [`Shape::paths_for_view`](crate::Shape::paths_for_view)
+/// so that shapes whose paths depend on the camera, such as silhouettes, can
+/// follow it without being rebuilt. When the view-projection matrix is
+/// known, shapes can also limit their paths to the visible volume, and with
+/// the viewport size to detail that is large enough to see.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{Matrix, Vector, ViewInfo};
+///
+/// let view = ViewInfo::new(Vector::new(4.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+/// let local = view.transform(&Matrix::translate(Vector::new(-1.0, 0.0, 0.0)));
+/// assert_eq!(local.eye, Vector::new(3.0, 0.0, 0.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewInfo {
+    /// The camera position.
+    pub eye: Vector,
+    /// The up direction.
+    pub up: Vector,
+    /// The view-projection matrix, if known. It maps positions to normalized
+    /// device coordinates, where the visible volume is the cube from -1 to 1.
+    pub matrix: Option<Matrix>,
+    /// The width and height of the viewport in pixels, if known.
+    pub viewport: Option<(f64, f64)>,
+}
+
+impl ViewInfo {
+    /// Creates a view from a camera position and up direction.
+    pub fn new(eye: Vector, up: Vector) -> Self {
+        ViewInfo {
+            eye,
+            up,
+            matrix: None,
+            viewport: None,
+        }
+    }
+
+    /// Sets the view-projection matrix.
+    pub fn with_matrix(mut self, matrix: Matrix) -> Self {
+        self.matrix = Some(matrix);
+        self
+    }
+
+    /// Sets the width and height of the viewport in pixels.
+    pub fn with_viewport(mut self, width: f64, height: f64) -> Self {
+        self.viewport = Some((width, height));
+        self
+    }
+
+    /// Returns the view mapped by `matrix`, such as into the local
+    /// coordinates of a transformed shape.
+    pub fn transform(&self, matrix: &Matrix) -> ViewInfo {
+        ViewInfo {
+            eye: matrix.mul_position(self.eye),
+            up: matrix.mul_direction(self.up),
+            matrix: self.matrix.map(|m| m.mul(&matrix.inverse())),
+            viewport: self.viewport,
+        }
+    }
+}
+
+/// The projection used by a [`Camera`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective projection with a vertical field of view in degrees.
+    Perspective {
+        /// Vertical field of view in degrees.
+        fovy: f64,
+    },
+    /// Orthographic projection showing `height` world units vertically.
+    ///
+    /// The horizontal extent follows from the viewport aspect ratio.
+    Orthographic {
+        /// Height of the visible area in world units.
+        height: f64,
+    },
+    /// Asymmetric perspective projection given by the extents of the near plane.
+    Frustum {
+        /// Left edge of the near plane.
+        left: f64,
+        /// Right edge of the near plane.
+        right: f64,
+        /// Bottom edge of the near plane.
+        bottom: f64,
+        /// Top edge of the near plane.
+        top: f64,
+    },
+}
+
+/// A camera describing how a scene is viewed.
+///
+/// A `Camera` replaces the long positional argument list of
+/// [`Scene::render`](crate::Scene::render). Create one with
+/// [`perspective`](Camera::perspective), [`orthographic`](Camera::orthographic)
+/// or [`frustum`](Camera::frustum) and adjust the viewport and clip distances
+/// with the `with_*` builder methods.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{Camera, Vector};
+///
+/// let camera = Camera::orthographic(
+///     Vector::new(10.0, 10.0, 10.0),
+///     Vector::new(0.0, 0.0, 0.0),
+///     Vector::new(0.0, 0.0, 1.0),
+///     4.0,
+/// )
+/// .with_viewport(800.0, 600.0)
+/// .with_clip(1.0, 50.0);
+///
+/// assert_eq!(camera.width, 800.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// The camera position.
+    pub eye: Vector,
+    /// The point the camera looks at.
+    pub center: Vector,
+    /// The up direction.
+    pub up: Vector,
+    /// The projection.
+    pub projection: Projection,
+    /// Output width in pixels.
+    pub width: f64,
+    /// Output height in pixels.
+    pub height: f64,
+    /// Near clipping plane distance.
+    pub near: f64,
+    /// Far clipping plane distance.
+    pub far: f64,
+}
+
+impl Camera {
+    /// Creates a camera with the given view and projection.
+    ///
+    /// The viewport defaults to 1024x1024 pixels and the clip distances
+    /// to `0.1` and `100.0`.
+    pub fn new(eye: Vector, center: Vector, up: Vector, projection: Projection) -> Self {
+        Camera {
+            eye,
+            center,
+            up,
+            projection,
+            width: 1024.0,
+            height: 1024.0,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+
+    /// Creates a perspective camera with a vertical field of view in degrees.
+    pub fn perspective(eye: Vector, center: Vector, up: Vector, fovy: f64) -> Self {
+        Camera::new(eye, center, up, Projection::Perspective { fovy })
+    }
+
+    /// Creates an orthographic camera showing `height` world units vertically.
+    pub fn orthographic(eye: Vector, center: Vector, up: Vector, height: f64) -> Self {
+        Camera::new(eye, center, up, Projection::Orthographic { height })
+    }
+
+    /// Creates a perspective camera from the extents of the near plane.
+    pub fn frustum(
+        eye: Vector,
+        center: Vector,
+        up: Vector,
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+    ) -> Self {
+        Camera::new(
+            eye,
+            center,
+            up,
+            Projection::Frustum {
+                left,
+                right,
+                bottom,
+                top,
+            },
+        )
+    }
+
+    /// Sets the output size in pixels.
+    pub fn with_viewport(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the near and far clipping plane distances.
+    pub fn with_clip(mut self, near: f64, far: f64) -> Self {
+        self.near = near;
+        self.far = far;
+        self
+    }
+
+    /// Returns the viewpoint of the camera.
+    pub fn view(&self) -> ViewInfo {
+        ViewInfo::new(self.eye, self.up)
+            .with_matrix(self.matrix())
+            .with_viewport(self.width, self.height)
+    }
+
+    /// Returns the aspect ratio (width / height) of the viewport.
+    pub fn aspect(&self) -> f64 {
+        self.width / self.height
+    }
+
+    /// Returns the combined view and projection matrix.
+    ///
+    /// This is the matrix expected by
+    /// [`Scene::render_with_matrix`](crate::Scene::render_with_matrix).
+    pub fn matrix(&self) -> Matrix {
+        let matrix = Matrix::look_at(self.eye, self.center, self.up);
+        match self.projection {
+            Projection::Perspective { fovy } => {
+                matrix.with_perspective(fovy, self.aspect(), self.near, self.far)
+            }
+            Projection::Orthographic { height } => {
+                let h = height / 2.0;
+                let w = h * self.aspect();
+                matrix.with_orthographic(-w, w, -h, h, self.near, self.far)
+            }
+            Projection::Frustum {
+                left,
+                right,
+                bottom,
+                top,
+            } => matrix.with_frustum(left, right, bottom, top, self.near, self.far),
+        }
+    }
+}
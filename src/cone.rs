@@ -1,4 +1,7 @@
 use crate::bounding_box::Box;
+use crate::camera::{Camera, ViewInfo};
+use crate::common::EPS;
+use crate::cylinder::{cap_paths, intersect_cap, CapTexture};
 use crate::hit::Hit;
 use crate::matrix::Matrix;
 use crate::path::Paths;
@@ -8,15 +11,61 @@ use crate::util::radians;
 use crate::vector::Vector;
 use std::sync::Arc;
 
+/// A cone around the Z axis with its base at z=0 and its apex at z=height.
+///
+/// The base is open unless [`capped`](Cone::capped) is set. Only a capped
+/// cone is a solid: an open one contains no points, so clip planes cut it
+/// open and boolean operations treat it as a surface.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{CapTexture, Cone, Ray, Shape, Vector};
+///
+/// let cone = Cone::new(1.0, 2.0)
+///     .with_capped(true)
+///     .with_cap_texture(CapTexture::Radial(12));
+///
+/// let ray = Ray::new(Vector::new(0.0, 0.0, -1.0), Vector::new(0.0, 0.0, 1.0));
+/// assert_eq!(cone.intersect(ray).t, 1.0);
+/// assert!(cone.contains(Vector::new(0.0, 0.0, 1.0), 0.0));
+/// ```
 #[derive(Debug, Clone)]
 pub struct Cone {
     pub radius: f64,
     pub height: f64,
+    /// Whether the base is closed with a disc.
+    pub capped: bool,
+    /// The texture style for the base, drawn only when it is capped.
+    pub cap_texture: CapTexture,
 }
 
 impl Cone {
     pub fn new(radius: f64, height: f64) -> Self {
-        Cone { radius, height }
+        Cone {
+            radius,
+            height,
+            capped: false,
+            cap_texture: CapTexture::default(),
+        }
+    }
+
+    /// Sets whether the base is closed with a disc.
+    pub fn with_capped(mut self, capped: bool) -> Self {
+        self.capped = capped;
+        self
+    }
+
+    /// Sets the texture style for the base, drawn only when it is capped.
+    pub fn with_cap_texture(mut self, cap_texture: CapTexture) -> Self {
+        self.cap_texture = cap_texture;
+        self
+    }
+
+    fn hit(&self, p: Vector, t: f64) -> Hit {
+        let k = self.radius / self.height;
+        let normal = Vector::new(p.x, p.y, -k * k * (p.z - self.height)).normalize();
+        Hit::new(t).with_surface(p, normal)
     }
 }
 
@@ -26,11 +75,47 @@ impl Shape for Cone {
         Box::new(Vector::new(-r, -r, 0.0), Vector::new(r, r, self.height))
     }
 
-    fn contains(&self, _v: Vector, _f: f64) -> bool {
-        false
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        if !self.capped || self.height <= 0.0 || v.z < -f || v.z > self.height + f {
+            return false;
+        }
+        let r = self.radius * (1.0 - v.z / self.height);
+        (v.x * v.x + v.y * v.y).sqrt() <= r.max(0.0) + f
     }
 
     fn intersect(&self, ray: Ray) -> Hit {
+        let hit = self.intersect_side(ray);
+        if !self.capped {
+            return hit;
+        }
+        hit.min(intersect_cap(ray, 0.0, self.radius, -1.0))
+    }
+
+    fn paths(&self) -> Paths {
+        let mut result = Vec::new();
+        let mut a = 0;
+        while a < 360 {
+            let x = self.radius * radians(a as f64).cos();
+            let y = self.radius * radians(a as f64).sin();
+            result.push(vec![
+                Vector::new(x, y, 0.0),
+                Vector::new(0.0, 0.0, self.height),
+            ]);
+            a += 30;
+        }
+        if self.capped {
+            result.extend(cap_paths(self.cap_texture, self.radius, 0.0));
+        }
+        Paths::from_vec(result)
+    }
+}
+
+impl Cone {
+    /// Intersects the curved side only.
+    fn intersect_side(&self, ray: Ray) -> Hit {
+        if self.height <= 0.0 {
+            return Hit::no_hit();
+        }
         let o = ray.origin;
         let d = ray.direction;
         let r = self.radius;
@@ -59,29 +144,176 @@ impl Shape for Cone {
         if t0 > 1e-6 {
             let p = ray.position(t0);
             if p.z > 0.0 && p.z < h {
-                return Hit::new(t0);
+                return self.hit(p, t0);
             }
         }
         if t1 > 1e-6 {
             let p = ray.position(t1);
             if p.z > 0.0 && p.z < h {
-                return Hit::new(t1);
+                return self.hit(p, t1);
+            }
+        }
+        Hit::no_hit()
+    }
+}
+
+/// A truncated cone (frustum) around the Z axis, from a base of radius
+/// `radius0` at z=0 to a top of radius `radius1` at z=height.
+///
+/// Either radius may be the larger one, which makes the shape useful for
+/// bolt heads, lampshades and vases. The default paths are lines along the
+/// side. The ends are open unless [`capped`](TruncatedCone::capped) is set,
+/// and as with [`Cone`] only a capped truncated cone contains points.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{CapTexture, Ray, Shape, TruncatedCone, Vector};
+///
+/// let shade = TruncatedCone::new(1.0, 0.5, 1.0)
+///     .with_capped(true)
+///     .with_cap_texture(CapTexture::Concentric(3));
+///
+/// let ray = Ray::new(Vector::new(3.0, 0.0, 0.5), Vector::new(-1.0, 0.0, 0.0));
+/// assert!((shade.intersect(ray).t - 2.25).abs() < 1e-9);
+/// assert!(shade.contains(Vector::new(0.0, 0.7, 0.5), 0.0));
+/// assert!(!shade.contains(Vector::new(0.0, 0.8, 0.5), 0.0));
+///
+/// // A ray parallel to a line of the side crosses it once.
+/// let d = Vector::new(-0.5, 0.0, 1.0).normalize();
+/// let hit = shade.intersect(Ray::new(Vector::new(0.0, 0.5, 0.0), d));
+/// assert!((hit.t - 0.75 * 1.25f64.sqrt()).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TruncatedCone {
+    /// The radius of the base at z=0.
+    pub radius0: f64,
+    /// The radius of the top at z=height.
+    pub radius1: f64,
+    /// The height of the frustum.
+    pub height: f64,
+    /// Whether the ends are closed with discs.
+    pub capped: bool,
+    /// The texture style for the ends, drawn only when they are capped.
+    pub cap_texture: CapTexture,
+}
+
+impl TruncatedCone {
+    /// Creates a new truncated cone with the given base and top radius and
+    /// height.
+    pub fn new(radius0: f64, radius1: f64, height: f64) -> Self {
+        TruncatedCone {
+            radius0,
+            radius1,
+            height,
+            capped: false,
+            cap_texture: CapTexture::default(),
+        }
+    }
+
+    /// Sets whether the ends are closed with discs.
+    pub fn with_capped(mut self, capped: bool) -> Self {
+        self.capped = capped;
+        self
+    }
+
+    /// Sets the texture style for the ends, drawn only when they are capped.
+    pub fn with_cap_texture(mut self, cap_texture: CapTexture) -> Self {
+        self.cap_texture = cap_texture;
+        self
+    }
+
+    /// Returns the radius at height `z`.
+    fn radius_at(&self, z: f64) -> f64 {
+        self.radius0 + (self.radius1 - self.radius0) * z / self.height
+    }
+
+    /// Intersects the curved side only.
+    fn intersect_side(&self, ray: Ray) -> Hit {
+        if self.height <= 0.0 {
+            return Hit::no_hit();
+        }
+        // x^2 + y^2 = (r0 + k z)^2 along the ray
+        let o = ray.origin;
+        let d = ray.direction;
+        let k = (self.radius1 - self.radius0) / self.height;
+        let ro = self.radius_at(o.z);
+
+        let a = d.x * d.x + d.y * d.y - k * k * d.z * d.z;
+        let b = 2.0 * (o.x * d.x + o.y * d.y - k * d.z * ro);
+        let c = o.x * o.x + o.y * o.y - ro * ro;
+
+        let roots = if a.abs() < EPS {
+            // The ray is parallel to a line of the side, which it crosses
+            // at most once.
+            if b == 0.0 {
+                return Hit::no_hit();
+            }
+            let t = -c / b;
+            [t, t]
+        } else {
+            let q = b * b - 4.0 * a * c;
+            if q < 0.0 {
+                return Hit::no_hit();
+            }
+            let s = q.sqrt();
+            let t0 = (-b + s) / (2.0 * a);
+            let t1 = (-b - s) / (2.0 * a);
+            [t0.min(t1), t0.max(t1)]
+        };
+
+        for t in roots {
+            if t <= 1e-6 {
+                continue;
+            }
+            let p = ray.position(t);
+            let r = self.radius_at(p.z);
+            if p.z > 0.0 && p.z < self.height && r >= 0.0 {
+                let normal = Vector::new(p.x, p.y, -k * r).normalize();
+                return Hit::new(t).with_surface(p, normal);
             }
         }
         Hit::no_hit()
     }
+}
+
+impl Shape for TruncatedCone {
+    fn bounding_box(&self) -> Box {
+        let r = self.radius0.max(self.radius1);
+        Box::new(Vector::new(-r, -r, 0.0), Vector::new(r, r, self.height))
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        if !self.capped || self.height <= 0.0 || v.z < -f || v.z > self.height + f {
+            return false;
+        }
+        let r = self.radius_at(v.z.clamp(0.0, self.height));
+        (v.x * v.x + v.y * v.y).sqrt() <= r + f
+    }
+
+    fn intersect(&self, ray: Ray) -> Hit {
+        let hit = self.intersect_side(ray);
+        if !self.capped {
+            return hit;
+        }
+        hit.min(intersect_cap(ray, 0.0, self.radius0, -1.0))
+            .min(intersect_cap(ray, self.height, self.radius1, 1.0))
+    }
 
     fn paths(&self) -> Paths {
         let mut result = Vec::new();
         let mut a = 0;
         while a < 360 {
-            let x = self.radius * radians(a as f64).cos();
-            let y = self.radius * radians(a as f64).sin();
+            let (s, c) = radians(a as f64).sin_cos();
             result.push(vec![
-                Vector::new(x, y, 0.0),
-                Vector::new(0.0, 0.0, self.height),
+                Vector::new(self.radius0 * c, self.radius0 * s, 0.0),
+                Vector::new(self.radius1 * c, self.radius1 * s, self.height),
             ]);
-            a += 30;
+            a += 10;
+        }
+        if self.capped {
+            result.extend(cap_paths(self.cap_texture, self.radius0, 0.0));
+            result.extend(cap_paths(self.cap_texture, self.radius1, self.height));
         }
         Paths::from_vec(result)
     }
@@ -102,6 +334,11 @@ impl OutlineCone {
             up,
         }
     }
+
+    /// Creates a new outline cone using the eye and up vectors of a [`Camera`].
+    pub fn from_camera(camera: &Camera, radius: f64, height: f64) -> Self {
+        OutlineCone::new(camera.eye, camera.up, radius, height)
+    }
 }
 
 impl Shape for OutlineCone {
@@ -118,6 +355,17 @@ impl Shape for OutlineCone {
     }
 
     fn paths(&self) -> Paths {
+        self.outline(self.eye)
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.outline(view.eye)
+    }
+}
+
+impl OutlineCone {
+    /// Returns the outline as seen from `eye`.
+    fn outline(&self, eye: Vector) -> Paths {
         // For a cone with apex at (0,0,h) and base circle radius r at z=0,
         // the silhouette generators are found by solving:
         // E.x * cos(θ) + E.y * sin(θ) = r * (1 - E.z / h)
@@ -128,9 +376,9 @@ impl Shape for OutlineCone {
         let r = self.cone.radius;
         let h = self.cone.height;
 
-        let a = self.eye.x;
-        let b = self.eye.y;
-        let c = r * (1.0 - self.eye.z / h);
+        let a = eye.x;
+        let b = eye.y;
+        let c = r * (1.0 - eye.z / h);
 
         let sqrt_ab = (a * a + b * b).sqrt();
 
@@ -143,7 +391,7 @@ impl Shape for OutlineCone {
             // Eye is inside the extended cone surface - no proper silhouette
             // Fall back to just the base circle
             let mut p0 = Vec::new();
-            let vscale = if self.eye.z >= 0.0 && self.eye.z <= h {
+            let vscale = if eye.z >= 0.0 && eye.z <= h {
                 1.0
             } else {
                 vscale
@@ -196,6 +444,28 @@ pub fn new_transformed_cone(up: Vector, v0: Vector, v1: Vector, radius: f64) ->
     TransformedShape::new(Arc::new(c), m)
 }
 
+/// Creates a truncated cone from `v0` with radius `radius0` to `v1` with
+/// radius `radius1`.
+pub fn new_transformed_truncated_cone(
+    up: Vector,
+    v0: Vector,
+    v1: Vector,
+    radius0: f64,
+    radius1: f64,
+) -> TransformedShape {
+    let d = v1.sub(v0);
+    let z = d.length();
+    let a = d.normalize().dot(up).acos();
+    let m = if a != 0.0 {
+        let u = d.cross(up).normalize();
+        Matrix::rotate(u, a).translated(v0)
+    } else {
+        Matrix::translate(v0)
+    };
+    let c = TruncatedCone::new(radius0, radius1, z);
+    TransformedShape::new(Arc::new(c), m)
+}
+
 pub fn new_transformed_outline_cone(
     eye: Vector,
     up: Vector,
@@ -215,3 +485,16 @@ pub fn new_transformed_outline_cone(
     let c = OutlineCone::new(m.inverse().mul_position(eye), up, radius, z);
     TransformedShape::new(Arc::new(c), m)
 }
+
+/// Creates an outline cone between two arbitrary points, using the eye and
+/// up vectors of a [`Camera`].
+///
+/// See [`new_transformed_outline_cone`].
+pub fn new_transformed_outline_cone_from_camera(
+    camera: &Camera,
+    v0: Vector,
+    v1: Vector,
+    radius: f64,
+) -> TransformedShape {
+    new_transformed_outline_cone(camera.eye, camera.up, v0, v1, radius)
+}
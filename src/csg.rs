@@ -2,6 +2,7 @@
 //!
 //! This module provides functions for combining shapes using boolean operations:
 //!
+//! - [`new_union`]: Creates a shape that is the union of multiple shapes
 //! - [`new_intersection`]: Creates a shape that is the intersection of multiple shapes
 //! - [`new_difference`]: Creates a shape that subtracts shapes from the first one
 //!
@@ -30,24 +31,58 @@
 //! ```
 
 use crate::bounding_box::Box;
+use crate::camera::ViewInfo;
 use crate::filter::Filter;
 use crate::hit::Hit;
-use crate::path::Paths;
+use crate::path::{Path, Paths};
 use crate::ray::Ray;
 use crate::shape::{EmptyShape, Shape};
 use crate::vector::Vector;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 /// Boolean operation type for CSG.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Op {
+    /// Union: keeps the volume that is inside either shape.
+    Union,
     /// Intersection: keeps only the volume that is inside both shapes.
     Intersection,
     /// Difference: subtracts the second shape from the first.
     Difference,
 }
 
+/// Largest number of grid points along one side of the grid used to find
+/// seams, whatever the tolerance.
+const MAX_SEAM_SAMPLES: usize = 1024;
+
+/// Number of bisection steps used to refine each seam crossing.
+const SEAM_REFINE: usize = 10;
+
 /// A shape created by combining two shapes with a boolean operation.
+///
+/// The paths of a boolean shape are the operands' own lines that lie on the
+/// result. The seams where the operands' surfaces meet can be added with
+/// [`BooleanShape::with_seam_tolerance`].
+///
+/// When a ray first meets an operand's surface that is not part of the
+/// result, the search continues past it, and the hit returned is still
+/// measured from the origin of the original ray.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{BooleanShape, Cube, Op, Ray, Shape, Sphere, Vector};
+/// use std::sync::Arc;
+///
+/// let sphere = Arc::new(Sphere::new(Vector::default(), 1.0));
+/// let slab = Arc::new(Cube::new(Vector::new(-0.5, -2.0, -2.0), Vector::new(0.5, 2.0, 2.0)));
+/// let shape = BooleanShape::new(Op::Intersection, sphere, slab);
+///
+/// // The sphere is met at x = -1, outside the slab, so the hit is the
+/// // slab's face at x = -0.5.
+/// let hit = shape.intersect(Ray::new(Vector::new(-10.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)));
+/// assert!((hit.t - 9.5).abs() < 1e-9);
+/// ```
 pub struct BooleanShape {
     /// The operation to perform.
     pub op: Op,
@@ -55,15 +90,249 @@ pub struct BooleanShape {
     pub a: Arc<dyn Shape + Send + Sync>,
     /// The second operand shape.
     pub b: Arc<dyn Shape + Send + Sync>,
+    /// The sampling distance of the seams between the operands. Seams are
+    /// not drawn when this is `0.0` or less, the default.
+    pub seam_tolerance: f64,
+    /// The seams traced by the first call to [`BooleanShape::seams`], with
+    /// the tolerance they were traced at.
+    seams: OnceLock<(f64, Paths)>,
 }
 
 impl BooleanShape {
-    /// Creates a new boolean shape.
+    /// Creates a new boolean shape, without seams.
     pub fn new(op: Op, a: Arc<dyn Shape + Send + Sync>, b: Arc<dyn Shape + Send + Sync>) -> Self {
-        BooleanShape { op, a, b }
+        BooleanShape {
+            op,
+            a,
+            b,
+            seam_tolerance: 0.0,
+            seams: OnceLock::new(),
+        }
+    }
+
+    /// Draws the seams between the operands, sampled every `tolerance`, or
+    /// disables them with `0.0`.
+    ///
+    /// Tracing the seams casts rays over a grid with this spacing, so small
+    /// tolerances are slow to trace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{BooleanShape, Cube, Op, Shape, Sphere, Vector};
+    /// use std::sync::Arc;
+    ///
+    /// let cube = Arc::new(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+    /// let sphere = Arc::new(Sphere::new(Vector::new(1.0, 1.0, 1.0), 0.5));
+    /// let shape = BooleanShape::new(Op::Difference, cube, sphere).with_seam_tolerance(0.05);
+    ///
+    /// // Quarter circles where the sphere cuts the cube's faces.
+    /// let seams = shape.seams();
+    /// assert!(!seams.paths.is_empty());
+    /// for v in seams.paths.iter().flatten() {
+    ///     assert!((v.distance(Vector::new(1.0, 1.0, 1.0)) - 0.5).abs() < 0.05);
+    /// }
+    /// ```
+    pub fn with_seam_tolerance(mut self, tolerance: f64) -> Self {
+        self.seam_tolerance = tolerance;
+        self
+    }
+
+    /// Returns the seams where the surfaces of the two operands meet.
+    ///
+    /// The surface of `a` is sampled on a grid with a spacing of
+    /// [`seam_tolerance`](BooleanShape::seam_tolerance) by casting rays
+    /// along the six axis directions, and the seam is traced where the
+    /// samples cross into `b`. Each surface point is only sampled from the
+    /// direction its normal faces most, so that no seam is drawn twice.
+    /// Parts of `a` that are hidden from all six directions are missed.
+    ///
+    /// The seams are traced by the first call and reused by every later call
+    /// with the same tolerance, so that rendering the shape from several
+    /// views, or as an operand of another boolean shape, does not trace them
+    /// again. Changing the tolerance traces them again until the shape is
+    /// recompiled with [`compile`](Shape::compile), which caches them anew.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{BooleanShape, Cube, Op, Shape, Sphere, Vector};
+    /// use std::sync::Arc;
+    ///
+    /// let cube = Arc::new(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+    /// let sphere = Arc::new(Sphere::new(Vector::new(1.0, 1.0, 1.0), 0.5));
+    /// let mut shape = BooleanShape::new(Op::Difference, cube, sphere).with_seam_tolerance(0.01);
+    /// let fine = shape.seams();
+    ///
+    /// // A new tolerance traces the seams again.
+    /// shape.seam_tolerance = 0.1;
+    /// assert!(shape.seams().paths.len() < fine.paths.len());
+    /// ```
+    pub fn seams(&self) -> Paths {
+        let (tolerance, seams) = self
+            .seams
+            .get_or_init(|| (self.seam_tolerance, self.trace_seams()));
+        if *tolerance == self.seam_tolerance {
+            seams.clone()
+        } else {
+            self.trace_seams()
+        }
+    }
+
+    /// Traces the seams, as described in [`BooleanShape::seams`].
+    fn trace_seams(&self) -> Paths {
+        let tolerance = self.seam_tolerance;
+        if tolerance <= 0.0 {
+            return Paths::new();
+        }
+        let ba = self.a.bounding_box();
+        let bb = self.b.bounding_box();
+        let min = ba.min.max(bb.min).sub_scalar(tolerance);
+        let max = ba.max.min(bb.max).add_scalar(tolerance);
+        if min.x > max.x || min.y > max.y || min.z > max.z {
+            return Paths::new();
+        }
+        let overlap = Box::new(min, max);
+
+        let mut result = Vec::new();
+        for axis in 0..3 {
+            for sign in [1.0, -1.0] {
+                result.extend(self.seams_from(axis, sign, &overlap, ba));
+            }
+        }
+        Paths::from_vec(result)
+    }
+
+    /// Traces the seams on the part of `a` seen along `sign` times the
+    /// `axis` direction.
+    fn seams_from(&self, axis: usize, sign: f64, overlap: &Box, ba: Box) -> Vec<Path> {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let lo = to_array(overlap.min);
+        let hi = to_array(overlap.max);
+        let start = if sign > 0.0 {
+            to_array(ba.min)[axis] - 1.0
+        } else {
+            to_array(ba.max)[axis] + 1.0
+        };
+        let mut direction = [0.0; 3];
+        direction[axis] = sign;
+        let direction = from_array(direction);
+
+        let steps = |k: usize| {
+            (((hi[k] - lo[k]) / self.seam_tolerance).ceil() as usize).clamp(1, MAX_SEAM_SAMPLES)
+        };
+        let (nu, nv) = (steps(u), steps(v));
+        let du = (hi[u] - lo[u]) / nu as f64;
+        let dv = (hi[v] - lo[v]) / nv as f64;
+
+        // Samples the surface of `a` at grid coordinates (i, j), returning
+        // the surface point and whether it is inside `b`.
+        let sample = |i: f64, j: f64| {
+            let mut origin = [0.0; 3];
+            origin[axis] = start;
+            origin[u] = lo[u] + i * du;
+            origin[v] = lo[v] + j * dv;
+            let r = Ray::new(from_array(origin), direction);
+            let hit = self.a.intersect(r);
+            if !hit.is_ok() {
+                return None;
+            }
+            let p = r.position(hit.t);
+            if !overlap.contains(p) {
+                return None;
+            }
+            if hit.normal != Vector::default() {
+                let n = to_array(hit.normal);
+                let dominant = (0..3)
+                    .max_by(|&x, &y| n[x].abs().partial_cmp(&n[y].abs()).unwrap())
+                    .unwrap_or(axis);
+                if dominant != axis || n[axis] * sign > 0.0 {
+                    return None;
+                }
+            }
+            Some((p, self.b.contains(p, 0.0)))
+        };
+
+        // Locates the seam between two grid positions on either side of it.
+        let crossing = |a: (f64, f64), b: (f64, f64), a_inside: bool, fallback: Vector| {
+            let (mut lo, mut hi) = (a, b);
+            let mut point = fallback;
+            for _ in 0..SEAM_REFINE {
+                let mid = ((lo.0 + hi.0) / 2.0, (lo.1 + hi.1) / 2.0);
+                let Some((p, inside)) = sample(mid.0, mid.1) else {
+                    break;
+                };
+                point = p;
+                if inside == a_inside {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            point
+        };
+
+        let grid: Vec<Vec<Option<(Vector, bool)>>> = (0..=nu)
+            .map(|i| (0..=nv).map(|j| sample(i as f64, j as f64)).collect())
+            .collect();
+
+        let mut result = Vec::new();
+        for i in 0..nu {
+            for j in 0..nv {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let Some(samples) = corners
+                    .iter()
+                    .map(|&(ci, cj)| grid[ci][cj])
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+                let states: Vec<bool> = samples.iter().map(|s| s.1).collect();
+                if states.iter().all(|s| *s) || states.iter().all(|s| !*s) {
+                    continue;
+                }
+                // Crossings on the edges whose corners differ, in order
+                // around the cell.
+                let edges: Vec<Vector> = (0..4)
+                    .filter(|&k| states[k] != states[(k + 1) % 4])
+                    .map(|k| {
+                        let (ai, aj) = corners[k];
+                        let (bi, bj) = corners[(k + 1) % 4];
+                        crossing(
+                            (ai as f64, aj as f64),
+                            (bi as f64, bj as f64),
+                            states[k],
+                            samples[k].0.add(samples[(k + 1) % 4].0).mul_scalar(0.5),
+                        )
+                    })
+                    .collect();
+                if edges.len() == 2 {
+                    result.push(vec![edges[0], edges[1]]);
+                } else if edges.len() == 4 {
+                    // Saddle: decide the pairing from the cell center.
+                    let center = sample(i as f64 + 0.5, j as f64 + 0.5);
+                    if center.map(|c| c.1) == Some(states[0]) {
+                        result.push(vec![edges[0], edges[1]]);
+                        result.push(vec![edges[2], edges[3]]);
+                    } else {
+                        result.push(vec![edges[3], edges[0]]);
+                        result.push(vec![edges[1], edges[2]]);
+                    }
+                }
+            }
+        }
+        result
     }
 }
 
+fn to_array(v: Vector) -> [f64; 3] {
+    [v.x, v.y, v.z]
+}
+
+fn from_array(a: [f64; 3]) -> Vector {
+    Vector::new(a[0], a[1], a[2])
+}
+
 /// Creates a boolean shape from multiple shapes.
 ///
 /// The shapes are combined pairwise using the given operation.
@@ -81,6 +350,56 @@ pub fn new_boolean_shape(
     shape
 }
 
+/// Creates a union of multiple shapes.
+///
+/// The resulting shape contains the volume that is inside any input shape.
+/// Lines of each shape that fall inside another are removed.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{new_difference, new_union, Sphere, Cube, Vector, Shape};
+/// use std::sync::Arc;
+///
+/// let sphere: Arc<dyn Shape + Send + Sync> = Arc::new(Sphere::new(Vector::new(0.0, 0.0, 1.0), 0.8));
+/// let cube: Arc<dyn Shape + Send + Sync> = Arc::new(Cube::new(
+///     Vector::new(-1.0, -1.0, -1.0),
+///     Vector::new(1.0, 1.0, 1.0),
+/// ));
+/// let hole: Arc<dyn Shape + Send + Sync> = Arc::new(Sphere::new(Vector::new(1.0, 0.0, 0.0), 0.5));
+///
+/// // (Sphere ∪ Cube) - Hole
+/// let shape = new_difference(vec![new_union(vec![sphere, cube]), hole]);
+/// assert!(shape.contains(Vector::new(0.0, 0.0, 1.7), 0.0));
+/// assert!(!shape.contains(Vector::new(0.9, 0.0, 0.0), 0.0));
+/// ```
+///
+/// Unions of more than two shapes keep the lines and surfaces of every
+/// operand:
+///
+/// ```
+/// use larnt::{new_union, Ray, Shape, Sphere, Vector};
+/// use std::sync::Arc;
+///
+/// let spheres: Vec<Arc<dyn Shape + Send + Sync>> = [0.0, 5.0, 10.0]
+///     .iter()
+///     .map(|&x| Arc::new(Sphere::new(Vector::new(x, 0.0, 0.0), 1.0)) as Arc<dyn Shape + Send + Sync>)
+///     .collect();
+/// let union = new_union(spheres);
+///
+/// let paths = union.paths();
+/// for x in [0.0, 5.0, 10.0] {
+///     let center = Vector::new(x, 0.0, 0.0);
+///     assert!(paths.paths.iter().flatten().any(|v| v.distance(center) < 1.01));
+/// }
+///
+/// let hit = union.intersect(Ray::new(Vector::new(-10.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)));
+/// assert!((hit.t - 9.0).abs() < 1e-6);
+/// ```
+pub fn new_union(shapes: Vec<Arc<dyn Shape + Send + Sync>>) -> Arc<dyn Shape + Send + Sync> {
+    new_boolean_shape(Op::Union, shapes)
+}
+
 /// Creates an intersection of multiple shapes.
 ///
 /// The resulting shape contains only the volume that is inside all input shapes.
@@ -134,6 +453,13 @@ impl Shape for BooleanShape {
         if let Some(s) = Arc::get_mut(&mut self.b) {
             s.compile();
         }
+        if self
+            .seams
+            .get()
+            .is_some_and(|(tolerance, _)| *tolerance != self.seam_tolerance)
+        {
+            self.seams = OnceLock::new();
+        }
     }
 
     fn bounding_box(&self) -> Box {
@@ -142,9 +468,10 @@ impl Shape for BooleanShape {
         a.extend(b)
     }
 
-    fn contains(&self, v: Vector, _f: f64) -> bool {
-        let f = 1e-3;
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        // Growing the result by `f` grows the subtracted shape by `-f`.
         match self.op {
+            Op::Union => self.a.contains(v, f) || self.b.contains(v, f),
             Op::Intersection => self.a.contains(v, f) && self.b.contains(v, f),
             Op::Difference => self.a.contains(v, f) && !self.b.contains(v, -f),
         }
@@ -156,25 +483,68 @@ impl Shape for BooleanShape {
         let h = h1.min(h2);
         let v = r.position(h.t);
 
-        if !h.is_ok() || self.contains(v, 0.0) {
+        if !h.is_ok() {
+            return h;
+        }
+        if self.keeps(v) {
+            // Surfaces of a subtracted shape face into the result.
+            if self.op == Op::Difference && h.t < h1.t {
+                return Hit {
+                    normal: h.normal.mul_scalar(-1.0),
+                    ..h
+                };
+            }
             return h;
         }
 
-        self.intersect(Ray::new(r.position(h.t + 0.01), r.direction))
+        let offset = h.t + 0.01;
+        let hit = self.intersect(Ray::new(r.position(offset), r.direction));
+        if !hit.is_ok() {
+            return hit;
+        }
+        Hit {
+            t: hit.t + offset,
+            ..hit
+        }
     }
 
     fn paths(&self) -> Paths {
-        let mut p = self.a.paths();
-        p.extend(self.b.paths());
+        self.combine(self.a.paths(), self.b.paths())
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.combine(self.a.paths_for_view(view), self.b.paths_for_view(view))
+    }
+}
+
+impl BooleanShape {
+    /// Keeps the parts of the operands' paths that lie on the result and
+    /// adds the seams.
+    fn combine(&self, a: Paths, b: Paths) -> Paths {
+        let mut p = a;
+        p.extend(b);
         p = p.chop(0.01);
         p = p.filter(self);
+        p.extend(self.seams());
         p
     }
 }
 
+impl BooleanShape {
+    /// Tests if a point on the surface of either operand lies on the
+    /// surface of the result.
+    fn keeps(&self, v: Vector) -> bool {
+        match self.op {
+            // Only surface points that are not inside the other operand.
+            Op::Union => !self.a.contains(v, -1e-3) && !self.b.contains(v, -1e-3),
+            Op::Intersection | Op::Difference => self.contains(v, 1e-3),
+        }
+    }
+}
+
 impl Filter for BooleanShape {
     fn filter(&self, v: Vector) -> Option<Vector> {
-        if self.contains(v, 0.0) {
+        if self.keeps(v) {
             Some(v)
         } else {
             None
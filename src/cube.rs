@@ -78,6 +78,26 @@ impl Cube {
         self.texture = texture;
         self
     }
+
+    fn hit(&self, r: Ray, t: f64) -> Hit {
+        let p = r.position(t);
+        // The face is the one the hit point is closest to.
+        let faces = [
+            (p.x - self.min.x, Vector::new(-1.0, 0.0, 0.0)),
+            (self.max.x - p.x, Vector::new(1.0, 0.0, 0.0)),
+            (p.y - self.min.y, Vector::new(0.0, -1.0, 0.0)),
+            (self.max.y - p.y, Vector::new(0.0, 1.0, 0.0)),
+            (p.z - self.min.z, Vector::new(0.0, 0.0, -1.0)),
+            (self.max.z - p.z, Vector::new(0.0, 0.0, 1.0)),
+        ];
+        let mut normal = faces[0];
+        for face in &faces[1..] {
+            if face.0.abs() < normal.0.abs() {
+                normal = *face;
+            }
+        }
+        Hit::new(t).with_surface(p, normal.1)
+    }
 }
 
 impl Shape for Cube {
@@ -106,10 +126,10 @@ impl Shape for Cube {
         let t1 = f.x.min(f.y).min(f.z);
 
         if t0 < 1e-3 && t1 > 1e-3 {
-            return Hit::new(t1);
+            return self.hit(r, t1);
         }
         if t0 >= 1e-3 && t0 < t1 {
-            return Hit::new(t0);
+            return self.hit(r, t0);
         }
         Hit::no_hit()
     }
@@ -2,7 +2,8 @@
 //!
 //! This module provides the [`Cylinder`] shape (aligned along the Z axis)
 //! and [`OutlineCylinder`] which renders as a silhouette from the camera's
-//! perspective.
+//! perspective. Cylinders can be closed with end caps, which are drawn with
+//! a [`CapTexture`].
 //!
 //! # Example
 //!
@@ -17,6 +18,7 @@
 //! ```
 
 use crate::bounding_box::Box;
+use crate::camera::{Camera, ViewInfo};
 use crate::hit::Hit;
 use crate::matrix::Matrix;
 use crate::path::Paths;
@@ -26,18 +28,46 @@ use crate::util::radians;
 use crate::vector::Vector;
 use std::sync::Arc;
 
+/// Texture style for the end caps of cylinders and cones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapTexture {
+    /// No cap paths (default)
+    #[default]
+    None,
+    /// The rim circle only
+    Rim,
+    /// The rim circle and the given number of spokes from the center
+    Radial(u32),
+    /// The given number of evenly spaced concentric circles, the outermost
+    /// being the rim
+    Concentric(u32),
+}
+
 /// A cylinder aligned along the Z axis.
 ///
 /// The cylinder is defined by its radius and Z-range. The default paths
 /// are vertical lines around the circumference.
 ///
+/// By default the ends are open, so rays pass through them. Use
+/// [`with_capped`](Cylinder::with_capped) to close them with discs and
+/// [`with_cap_texture`](Cylinder::with_cap_texture) to draw them.
+///
 /// # Example
 ///
 /// ```
-/// use larnt::{Cylinder, Vector};
+/// use larnt::{CapTexture, Cylinder, Ray, Shape, Vector};
 ///
 /// // Cylinder with radius 0.5, from z=-1 to z=1
 /// let cylinder = Cylinder::new(0.5, -1.0, 1.0);
+///
+/// // The same cylinder closed at both ends, with rim circles
+/// let capped = Cylinder::new(0.5, -1.0, 1.0)
+///     .with_capped(true)
+///     .with_cap_texture(CapTexture::Rim);
+///
+/// let ray = Ray::new(Vector::new(0.0, 0.0, 3.0), Vector::new(0.0, 0.0, -1.0));
+/// assert!(!cylinder.intersect(ray).is_ok());
+/// assert_eq!(capped.intersect(ray).t, 2.0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Cylinder {
@@ -47,30 +77,43 @@ pub struct Cylinder {
     pub z0: f64,
     /// The maximum Z coordinate.
     pub z1: f64,
+    /// Whether the ends are closed with discs.
+    pub capped: bool,
+    /// The texture style for the ends, drawn only when they are capped.
+    pub cap_texture: CapTexture,
 }
 
 impl Cylinder {
     /// Creates a new cylinder with the given radius and Z-range.
     pub fn new(radius: f64, z0: f64, z1: f64) -> Self {
-        Cylinder { radius, z0, z1 }
+        Cylinder {
+            radius,
+            z0,
+            z1,
+            capped: false,
+            cap_texture: CapTexture::default(),
+        }
     }
-}
 
-impl Shape for Cylinder {
-    fn bounding_box(&self) -> Box {
-        let r = self.radius;
-        Box::new(Vector::new(-r, -r, self.z0), Vector::new(r, r, self.z1))
+    /// Sets whether the ends are closed with discs.
+    pub fn with_capped(mut self, capped: bool) -> Self {
+        self.capped = capped;
+        self
     }
 
-    fn contains(&self, v: Vector, f: f64) -> bool {
-        let xy = Vector::new(v.x, v.y, 0.0);
-        if xy.length() > self.radius + f {
-            return false;
-        }
-        v.z >= self.z0 - f && v.z <= self.z1 + f
+    /// Sets the texture style for the ends, drawn only when they are capped.
+    pub fn with_cap_texture(mut self, cap_texture: CapTexture) -> Self {
+        self.cap_texture = cap_texture;
+        self
     }
 
-    fn intersect(&self, ray: Ray) -> Hit {
+    fn hit(&self, r: Ray, t: f64) -> Hit {
+        let p = r.position(t);
+        Hit::new(t).with_surface(p, Vector::new(p.x, p.y, 0.0).normalize())
+    }
+
+    /// Intersects the curved side only.
+    fn intersect_side(&self, ray: Ray) -> Hit {
         let r = self.radius;
         let o = ray.origin;
         let d = ray.direction;
@@ -95,13 +138,37 @@ impl Shape for Cylinder {
         let z1 = o.z + t1 * d.z;
 
         if t0 > 1e-6 && self.z0 < z0 && z0 < self.z1 {
-            return Hit::new(t0);
+            return self.hit(ray, t0);
         }
         if t1 > 1e-6 && self.z0 < z1 && z1 < self.z1 {
-            return Hit::new(t1);
+            return self.hit(ray, t1);
         }
         Hit::no_hit()
     }
+}
+
+impl Shape for Cylinder {
+    fn bounding_box(&self) -> Box {
+        let r = self.radius;
+        Box::new(Vector::new(-r, -r, self.z0), Vector::new(r, r, self.z1))
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        let xy = Vector::new(v.x, v.y, 0.0);
+        if xy.length() > self.radius + f {
+            return false;
+        }
+        v.z >= self.z0 - f && v.z <= self.z1 + f
+    }
+
+    fn intersect(&self, ray: Ray) -> Hit {
+        let hit = self.intersect_side(ray);
+        if !self.capped {
+            return hit;
+        }
+        hit.min(intersect_cap(ray, self.z0, self.radius, -1.0))
+            .min(intersect_cap(ray, self.z1, self.radius, 1.0))
+    }
 
     fn paths(&self) -> Paths {
         let mut result = Vec::new();
@@ -112,14 +179,71 @@ impl Shape for Cylinder {
             result.push(vec![Vector::new(x, y, self.z0), Vector::new(x, y, self.z1)]);
             a += 10;
         }
+        if self.capped {
+            result.extend(cap_paths(self.cap_texture, self.radius, self.z0));
+            result.extend(cap_paths(self.cap_texture, self.radius, self.z1));
+        }
         Paths::from_vec(result)
     }
 }
 
+/// Intersects the disc of the given radius around the Z axis at height `z`,
+/// facing along `normal_z`.
+pub(crate) fn intersect_cap(ray: Ray, z: f64, radius: f64, normal_z: f64) -> Hit {
+    if ray.direction.z == 0.0 {
+        return Hit::no_hit();
+    }
+    let t = (z - ray.origin.z) / ray.direction.z;
+    if t <= 1e-6 {
+        return Hit::no_hit();
+    }
+    let p = ray.position(t);
+    if p.x * p.x + p.y * p.y > radius * radius {
+        return Hit::no_hit();
+    }
+    Hit::new(t).with_surface(Vector::new(p.x, p.y, z), Vector::new(0.0, 0.0, normal_z))
+}
+
+/// Returns the paths of a cap texture on the disc of the given radius
+/// around the Z axis at height `z`.
+pub(crate) fn cap_paths(texture: CapTexture, radius: f64, z: f64) -> Vec<Vec<Vector>> {
+    let circle = |r: f64| -> Vec<Vector> {
+        (0..=360)
+            .map(|a| {
+                let a = radians(a as f64);
+                Vector::new(r * a.cos(), r * a.sin(), z)
+            })
+            .collect()
+    };
+    let mut result = Vec::new();
+    match texture {
+        CapTexture::None => {}
+        CapTexture::Rim => result.push(circle(radius)),
+        CapTexture::Radial(n) => {
+            result.push(circle(radius));
+            for i in 0..n {
+                let a = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+                let p = Vector::new(radius * a.cos(), radius * a.sin(), z);
+                result.push(vec![Vector::new(0.0, 0.0, z), p]);
+            }
+        }
+        CapTexture::Concentric(n) => {
+            for i in 1..=n {
+                result.push(circle(radius * i as f64 / n as f64));
+            }
+        }
+    }
+    result
+}
+
 /// A cylinder that renders as a silhouette from the camera's perspective.
 ///
 /// Unlike [`Cylinder`] which draws vertical lines, `OutlineCylinder` draws
 /// only the visible outline of the cylinder as seen from the camera.
+///
+/// When rendered by a [`Scene`](crate::Scene), the outline follows the
+/// render camera (see [`Shape::paths_for_view`]). The stored `eye` and `up`
+/// are only used by [`Shape::paths`].
 #[derive(Debug, Clone)]
 pub struct OutlineCylinder {
     /// The underlying cylinder geometry.
@@ -139,6 +263,11 @@ impl OutlineCylinder {
             up,
         }
     }
+
+    /// Creates a new outline cylinder using the eye and up vectors of a [`Camera`].
+    pub fn from_camera(camera: &Camera, radius: f64, z0: f64, z1: f64) -> Self {
+        OutlineCylinder::new(camera.eye, camera.up, radius, z0, z1)
+    }
 }
 
 impl Shape for OutlineCylinder {
@@ -155,6 +284,17 @@ impl Shape for OutlineCylinder {
     }
 
     fn paths(&self) -> Paths {
+        self.outline(self.eye)
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.outline(view.eye)
+    }
+}
+
+impl OutlineCylinder {
+    /// Returns the outline as seen from `eye`.
+    fn outline(&self, eye: Vector) -> Paths {
         // For a cylinder with radius r aligned along Z-axis, the silhouette
         // generators are found by solving:
         // E.x * cos(θ) + E.y * sin(θ) = r
@@ -164,8 +304,8 @@ impl Shape for OutlineCylinder {
         // Solution: θ = atan2(b, a) ± acos(c / sqrt(a^2 + b^2))
         let r = self.cylinder.radius;
 
-        let a = self.eye.x;
-        let b = self.eye.y;
+        let a = eye.x;
+        let b = eye.y;
         let c = r;
 
         let sqrt_ab = (a * a + b * b).sqrt();
@@ -277,3 +417,16 @@ pub fn new_transformed_outline_cylinder(
     let c = OutlineCylinder::new(m.inverse().mul_position(eye), up, radius, 0.0, z);
     TransformedShape::new(Arc::new(c), m)
 }
+
+/// Creates an outline cylinder between two arbitrary points, using the eye
+/// and up vectors of a [`Camera`].
+///
+/// See [`new_transformed_outline_cylinder`].
+pub fn new_transformed_outline_cylinder_from_camera(
+    camera: &Camera,
+    v0: Vector,
+    v1: Vector,
+    radius: f64,
+) -> TransformedShape {
+    new_transformed_outline_cylinder(camera.eye, camera.up, v0, v1, radius)
+}
@@ -13,6 +13,37 @@ pub struct ClipFilter<'a> {
     pub scene: &'a Scene,
 }
 
+/// Sorts the points inside the clip box into visible and hidden ones, with
+/// a single visibility test per point.
+///
+/// This is used to render the visible and hidden lines of a scene in one
+/// pass.
+pub(crate) struct VisibilitySplit<'a> {
+    pub matrix: Matrix,
+    pub eye: Vector,
+    pub scene: &'a Scene,
+}
+
+impl VisibilitySplit<'_> {
+    /// Returns `Some((true, w))` for a visible point and `Some((false, w))`
+    /// for a hidden one, where `w` is the projected point, or `None` for a
+    /// point outside the clip box.
+    pub fn classify(&self, v: Vector) -> Option<(bool, Vector)> {
+        let w = self.matrix.mul_position_w(v);
+        if !CLIP_BOX.contains(w) {
+            return None;
+        }
+        Some((self.scene.visible(self.eye, v), w))
+    }
+}
+
+/// Keeps the points inside the clip box, without testing visibility.
+///
+/// This is used for annotations drawn on top of a scene.
+pub struct ViewFilter {
+    pub matrix: Matrix,
+}
+
 pub static CLIP_BOX: Box = Box {
     min: Vector {
         x: -1.0,
@@ -38,3 +69,13 @@ impl<'a> Filter for ClipFilter<'a> {
         Some(w)
     }
 }
+
+impl Filter for ViewFilter {
+    fn filter(&self, v: Vector) -> Option<Vector> {
+        let w = self.matrix.mul_position_w(v);
+        if !CLIP_BOX.contains(w) {
+            return None;
+        }
+        Some(w)
+    }
+}
@@ -77,7 +77,7 @@ where
         while t < 10.0 {
             let v = ray.position(t);
             if self.contains(v, 0.0) != sign && self.bx.contains(v) {
-                return Hit::new(t);
+                return Hit::new(t).with_surface(v, self.normal(v));
             }
             t += step;
         }
@@ -97,6 +97,18 @@ impl<F> Function<F>
 where
     F: Fn(f64, f64) -> f64 + Send + Sync,
 {
+    /// Returns the outward normal of the surface above or below `v`.
+    fn normal(&self, v: Vector) -> Vector {
+        let h = 1e-4;
+        let dx = ((self.func)(v.x + h, v.y) - (self.func)(v.x - h, v.y)) / (2.0 * h);
+        let dy = ((self.func)(v.x, v.y + h) - (self.func)(v.x, v.y - h)) / (2.0 * h);
+        let n = Vector::new(-dx, -dy, 1.0).normalize();
+        match self.direction {
+            Direction::Below => n,
+            Direction::Above => n.mul_scalar(-1.0),
+        }
+    }
+
     /// Calculate max radius for radial textures based on bbox dimensions
     fn max_radius(&self) -> f64 {
         (self.bx.max.x - self.bx.min.x).max(self.bx.max.y - self.bx.min.y) / 2.0
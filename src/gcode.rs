@@ -0,0 +1,267 @@
+//! G-code output for pen plotters and laser engravers.
+//!
+//! This module provides [`GcodeOptions`], which control how
+//! [`Paths::to_gcode`] turns rendered paths into G-code: how the pen is
+//! raised and lowered, the feed rates, and how render coordinates map to
+//! millimetres on the paper.
+//!
+//! The output only depends on the paths and the options, so it can be
+//! compared against a stored file.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Cube, GcodeOptions, PenLift, Scene, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+//!
+//! let paths = scene.render(
+//!     Vector::new(4.0, 3.0, 2.0),
+//!     Vector::new(0.0, 0.0, 0.0),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     1024.0, 1024.0, 50.0, 0.1, 10.0, 0.01,
+//! );
+//!
+//! // Fit the 1024 pixel render to 200 mm, with a servo-driven pen.
+//! let options = GcodeOptions::new()
+//!     .with_scale(200.0 / 1024.0)
+//!     .with_origin(10.0, 10.0)
+//!     .with_pen(PenLift::Command {
+//!         up: "M3 S30".to_string(),
+//!         down: "M3 S90".to_string(),
+//!     });
+//! let (paths, _) = paths.optimize_order();
+//! paths.write_to_gcode("output.gcode", &options).unwrap();
+//! ```
+
+use crate::path::Paths;
+use crate::vector::Vector;
+
+/// How the pen is raised and lowered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PenLift {
+    /// Move the Z axis to the `up` or `down` height, in millimetres.
+    ///
+    /// The pen is raised with a rapid move and lowered at the drawing feed
+    /// rate.
+    Z {
+        /// The Z height with the pen up.
+        up: f64,
+        /// The Z height with the pen down.
+        down: f64,
+    },
+    /// Emit the given commands, such as the M-codes driving a pen servo or
+    /// switching a laser. A command may span several lines.
+    Command {
+        /// The command that raises the pen.
+        up: String,
+        /// The command that lowers the pen.
+        down: String,
+    },
+}
+
+/// Options for G-code output.
+///
+/// Render coordinates are multiplied by [`scale`](GcodeOptions::scale) to
+/// give millimetres and then offset by [`origin`](GcodeOptions::origin).
+///
+/// The header and footer are templates in which `{pen_up}` and `{pen_down}`
+/// are replaced by the pen commands, and `{width}` and `{height}` by the
+/// size of the drawing in millimetres.
+///
+/// # Example
+///
+/// ```
+/// use larnt::GcodeOptions;
+///
+/// let options = GcodeOptions::new().with_feed_rates(1500.0, Some(6000.0));
+/// assert_eq!(options.draw_feed, 1500.0);
+/// assert!(options.header.starts_with("G21"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeOptions {
+    /// How the pen is raised and lowered.
+    pub pen: PenLift,
+    /// The feed rate of pen-down moves, in millimetres per minute.
+    pub draw_feed: f64,
+    /// The feed rate of pen-up moves, in millimetres per minute. `None`
+    /// uses rapid (`G0`) moves.
+    pub travel_feed: Option<f64>,
+    /// Millimetres per render unit.
+    pub scale: f64,
+    /// The position of the render origin on the paper, in millimetres.
+    pub origin: (f64, f64),
+    /// The number of decimal places of coordinates.
+    pub precision: usize,
+    /// The template written before the paths.
+    pub header: String,
+    /// The template written after the paths.
+    pub footer: String,
+}
+
+impl GcodeOptions {
+    /// Creates the default options: a Z-axis pen lifting to 5 mm, drawing
+    /// at 3000 mm/min with rapid travel, and one millimetre per render unit.
+    /// The default header selects millimetres and absolute positioning and
+    /// raises the pen, and the default footer returns to the machine origin.
+    pub fn new() -> Self {
+        GcodeOptions {
+            pen: PenLift::Z { up: 5.0, down: 0.0 },
+            draw_feed: 3000.0,
+            travel_feed: None,
+            scale: 1.0,
+            origin: (0.0, 0.0),
+            precision: 3,
+            header: "G21\nG90\n{pen_up}".to_string(),
+            footer: "G0 X0 Y0".to_string(),
+        }
+    }
+
+    /// Sets how the pen is raised and lowered.
+    pub fn with_pen(mut self, pen: PenLift) -> Self {
+        self.pen = pen;
+        self
+    }
+
+    /// Sets the pen-down and pen-up feed rates, in millimetres per minute.
+    pub fn with_feed_rates(mut self, draw: f64, travel: Option<f64>) -> Self {
+        self.draw_feed = draw;
+        self.travel_feed = travel;
+        self
+    }
+
+    /// Sets the number of millimetres per render unit.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the position of the render origin on the paper, in millimetres.
+    pub fn with_origin(mut self, x: f64, y: f64) -> Self {
+        self.origin = (x, y);
+        self
+    }
+
+    /// Sets the number of decimal places of coordinates.
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the header template. An empty template writes no header.
+    pub fn with_header(mut self, header: &str) -> Self {
+        self.header = header.to_string();
+        self
+    }
+
+    /// Sets the footer template. An empty template writes no footer.
+    pub fn with_footer(mut self, footer: &str) -> Self {
+        self.footer = footer.to_string();
+        self
+    }
+
+    fn pen_up(&self) -> String {
+        match &self.pen {
+            PenLift::Z { up, .. } => format!("G0 Z{}", self.number(*up)),
+            PenLift::Command { up, .. } => up.clone(),
+        }
+    }
+
+    fn pen_down(&self) -> String {
+        match &self.pen {
+            PenLift::Z { down, .. } => {
+                format!(
+                    "G1 Z{} F{}",
+                    self.number(*down),
+                    self.number(self.draw_feed)
+                )
+            }
+            PenLift::Command { down, .. } => down.clone(),
+        }
+    }
+
+    /// Formats a number with the configured precision, without a sign on
+    /// zero.
+    fn number(&self, x: f64) -> String {
+        let s = format!("{:.*}", self.precision, x);
+        match s.strip_prefix('-') {
+            Some(rest) if rest.chars().all(|c| c == '0' || c == '.') => rest.to_string(),
+            _ => s,
+        }
+    }
+
+    /// Formats the paper position of the render point `v`.
+    fn position(&self, v: Vector) -> String {
+        format!(
+            "X{} Y{}",
+            self.number(v.x * self.scale + self.origin.0),
+            self.number(v.y * self.scale + self.origin.1)
+        )
+    }
+
+    fn fill_template(&self, template: &str, paths: &Paths) -> String {
+        let size = paths.bounding_box().size().mul_scalar(self.scale);
+        template
+            .replace("{pen_up}", &self.pen_up())
+            .replace("{pen_down}", &self.pen_down())
+            .replace("{width}", &self.number(size.x))
+            .replace("{height}", &self.number(size.y))
+    }
+}
+
+impl Default for GcodeOptions {
+    fn default() -> Self {
+        GcodeOptions::new()
+    }
+}
+
+/// Converts `paths` to G-code, drawing them in order.
+///
+/// A path that starts where the previous one ended is drawn without lifting
+/// the pen, and the pen is raised after the last path.
+pub(crate) fn paths_to_gcode(paths: &Paths, options: &GcodeOptions) -> String {
+    let mut lines = Vec::new();
+    if !options.header.is_empty() {
+        lines.push(options.fill_template(&options.header, paths));
+    }
+    let pen_up = options.pen_up();
+    let pen_down = options.pen_down();
+    let travel = match options.travel_feed {
+        Some(feed) => format!(" F{}", options.number(feed)),
+        None => String::new(),
+    };
+    let travel_code = if options.travel_feed.is_some() {
+        "G1"
+    } else {
+        "G0"
+    };
+    let draw_feed = format!(" F{}", options.number(options.draw_feed));
+
+    // The position the pen is down at, if it is down.
+    let mut down_at: Option<String> = None;
+    for path in paths.paths.iter().filter(|p| !p.is_empty()) {
+        let start = options.position(path[0]);
+        if down_at.as_ref() != Some(&start) {
+            if down_at.is_some() {
+                lines.push(pen_up.clone());
+            }
+            lines.push(format!("{} {}{}", travel_code, start, travel));
+            lines.push(pen_down.clone());
+        }
+        for (i, v) in path.iter().enumerate().skip(1) {
+            let feed = if i == 1 { draw_feed.as_str() } else { "" };
+            lines.push(format!("G1 {}{}", options.position(*v), feed));
+        }
+        down_at = Some(options.position(path[path.len() - 1]));
+    }
+    if down_at.is_some() {
+        lines.push(pen_up);
+    }
+    if !options.footer.is_empty() {
+        lines.push(options.fill_template(&options.footer, paths));
+    }
+    let mut gcode = lines.join("\n");
+    gcode.push('\n');
+    gcode
+}
@@ -0,0 +1,637 @@
+//! Ground plane.
+//!
+//! This module provides the [`GroundPlane`] shape, a flat surface that can
+//! be bounded or extend to infinity. It hides whatever lies behind it and is
+//! drawn with a [`GroundTexture`].
+//!
+//! An infinite plane has no natural extent to draw, so its texture is only
+//! generated for a view: when rendered by a [`Scene`](crate::Scene), the
+//! texture is clipped to the visible volume of the camera (see
+//! [`Shape::paths_for_view`]), and lines that would be closer than a pixel
+//! on screen are left out, so the texture fades out toward the horizon
+//! instead of filling it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Camera, GroundPlane, GroundTexture, RenderOptions, Scene, Sphere, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Sphere::new(Vector::new(0.0, 0.0, 1.0), 1.0));
+//! scene.add(
+//!     GroundPlane::new(Vector::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0))
+//!         .with_texture(GroundTexture::Grid)
+//!         .with_spacing(0.5),
+//! );
+//!
+//! let camera = Camera::perspective(
+//!     Vector::new(6.0, 4.0, 3.0),
+//!     Vector::new(0.0, 0.0, 0.5),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     40.0,
+//! )
+//! .with_clip(0.1, 20.0);
+//!
+//! let paths = scene.render_camera(&camera, &RenderOptions::default());
+//! paths.write_to_svg("output.svg", camera.width, camera.height).unwrap();
+//! ```
+
+use crate::bounding_box::Box;
+use crate::camera::ViewInfo;
+use crate::common::INF;
+use crate::hit::Hit;
+use crate::matrix::Matrix;
+use crate::path::{Path, Paths};
+use crate::plane::Plane;
+use crate::ray::Ray;
+use crate::shape::Shape;
+use crate::vector::Vector;
+use std::f64::consts::{PI, SQRT_2};
+
+/// Smallest on-screen distance, in pixels, between texture lines. Where
+/// lines would be closer, they are left out.
+const MIN_LINE_PIXELS: f64 = 1.0;
+
+/// Number of samples along a texture line used to find where its spacing
+/// falls below [`MIN_LINE_PIXELS`].
+const FOOTPRINT_SAMPLES: usize = 16;
+
+/// Number of bisection steps used to refine where a texture line is cut.
+const FOOTPRINT_REFINE: usize = 16;
+
+/// A segment between two points in plane coordinates.
+type Segment = ((f64, f64), (f64, f64));
+
+/// Texture style for GroundPlane shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroundTexture {
+    /// Grid lines along both plane axes (default)
+    #[default]
+    Grid,
+    /// A checkerboard whose alternate squares are hatched diagonally
+    CheckerHatch,
+    /// Concentric rings around the plane's point
+    Rings,
+}
+
+/// A flat ground surface through a point, facing along a normal.
+///
+/// The plane extends to infinity unless limited with
+/// [`with_extent`](GroundPlane::with_extent). It is a surface without
+/// thickness: rays from either side stop at it, so it hides what lies
+/// below it.
+///
+/// The texture is laid out on two axes in the plane. For a horizontal plane
+/// these are the X and Y axes, so grid lines follow world coordinates.
+/// [`paths`](Shape::paths) of an infinite plane are empty; its texture is
+/// generated by [`paths_for_view`](Shape::paths_for_view), clipped to the
+/// visible volume. When the view's viewport is known, texture lines are
+/// only drawn where they are at least a pixel apart on screen.
+///
+/// The bounding box of an infinite plane is unbounded, so the
+/// [`Tree`](crate::Tree) of a scene keeps it out of its hierarchy and tests
+/// it separately.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{GroundPlane, GroundTexture, Ray, Shape, Vector};
+///
+/// let ground = GroundPlane::new(Vector::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0))
+///     .with_extent(2.0)
+///     .with_texture(GroundTexture::Grid);
+///
+/// let down = Vector::new(0.0, 0.0, -1.0);
+/// assert_eq!(ground.intersect(Ray::new(Vector::new(1.0, 1.0, 3.0), down)).t, 3.0);
+/// assert!(!ground.intersect(Ray::new(Vector::new(3.0, 0.0, 3.0), down)).is_ok());
+///
+/// // Five lines in each direction, plus the border
+/// assert_eq!(ground.paths().paths.len(), 14);
+/// ```
+///
+/// Seen from a camera, the lines of an infinite plane stop where they
+/// would merge:
+///
+/// ```
+/// use larnt::{Camera, GroundPlane, Shape, Vector, ViewInfo};
+///
+/// let ground = GroundPlane::new(Vector::default(), Vector::new(0.0, 0.0, 1.0)).with_spacing(0.1);
+/// let camera = Camera::perspective(
+///     Vector::new(0.0, -5.0, 1.0),
+///     Vector::new(0.0, 0.0, 0.0),
+///     Vector::new(0.0, 0.0, 1.0),
+///     50.0,
+/// )
+/// .with_clip(0.1, 1000.0);
+///
+/// let view = camera.view();
+/// let lines = ground.paths_for_view(&view);
+/// let all = ground.paths_for_view(&ViewInfo { viewport: None, ..view });
+/// assert!(lines.paths.len() < all.paths.len() / 4);
+/// ```
+///
+/// ```
+/// use larnt::{GroundPlane, Ray, Scene, Sphere, Vector};
+///
+/// let mut scene = Scene::new();
+/// scene.add(Sphere::new(Vector::new(0.0, 0.0, 1.0), 1.0));
+/// scene.add(GroundPlane::new(Vector::default(), Vector::new(0.0, 0.0, 1.0)));
+/// scene.compile();
+///
+/// let tree = scene.tree.as_ref().unwrap();
+/// assert!(tree.bx.max.x < 1e3 && tree.unbounded.len() == 1);
+/// let hit = scene.intersect(Ray::new(Vector::new(5.0, 0.0, 3.0), Vector::new(0.0, 0.0, -1.0)));
+/// assert_eq!(hit.shape, Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GroundPlane {
+    /// A point on the plane, the origin of the texture.
+    pub point: Vector,
+    /// The unit normal of the plane.
+    pub normal: Vector,
+    /// How far the plane extends from `point` along each axis, or `None`
+    /// for an infinite plane.
+    pub extent: Option<f64>,
+    /// The distance between grid lines or rings.
+    pub spacing: f64,
+    /// The texture style for the plane.
+    pub texture: GroundTexture,
+}
+
+impl GroundPlane {
+    /// Creates an infinite plane through `point` facing along `normal`.
+    pub fn new(point: Vector, normal: Vector) -> Self {
+        GroundPlane {
+            point,
+            normal: normal.normalize(),
+            extent: None,
+            spacing: 1.0,
+            texture: GroundTexture::default(),
+        }
+    }
+
+    /// Limits the plane to a square reaching `extent` from its point along
+    /// each axis.
+    pub fn with_extent(mut self, extent: f64) -> Self {
+        self.extent = Some(extent);
+        self
+    }
+
+    /// Sets the distance between grid lines or rings.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the texture style for the plane.
+    pub fn with_texture(mut self, texture: GroundTexture) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Returns the two texture axes in the plane.
+    fn axes(&self) -> (Vector, Vector) {
+        let n = self.normal;
+        let a = if n.x.abs() > 0.9 {
+            Vector::new(0.0, 1.0, 0.0)
+        } else {
+            Vector::new(1.0, 0.0, 0.0)
+        };
+        let u = a.sub(n.mul_scalar(n.dot(a))).normalize();
+        (u, n.cross(u))
+    }
+
+    /// Returns the plane coordinates of `v`.
+    fn to_plane(&self, v: Vector) -> (f64, f64) {
+        let (u, w) = self.axes();
+        let d = v.sub(self.point);
+        (d.dot(u), d.dot(w))
+    }
+
+    /// Returns the region of the plane to draw: the extent and, if a
+    /// view-projection matrix is given, the visible volume.
+    fn region(&self, matrix: Option<&Matrix>) -> Option<Region> {
+        let mut region = Region::default();
+        if let Some(e) = self.extent {
+            region
+                .edges
+                .extend([[1.0, 0.0, e], [-1.0, 0.0, e], [0.0, 1.0, e], [0.0, -1.0, e]]);
+        }
+        if let Some(m) = matrix {
+            // -w <= x, y, z <= w in clip space, with each clip coordinate a
+            // linear function of the plane coordinates.
+            let (u, w) = self.axes();
+            let rows = [
+                [m.x00, m.x01, m.x02, m.x03],
+                [m.x10, m.x11, m.x12, m.x13],
+                [m.x20, m.x21, m.x22, m.x23],
+            ];
+            let rw = [m.x30, m.x31, m.x32, m.x33];
+            for row in rows {
+                for sign in [1.0, -1.0] {
+                    let g: Vec<f64> = (0..4).map(|i| sign * row[i] - rw[i]).collect();
+                    let g3 = Vector::new(g[0], g[1], g[2]);
+                    region
+                        .edges
+                        .push([g3.dot(u), g3.dot(w), -(g3.dot(self.point) + g[3])]);
+                }
+            }
+        }
+        if region.edges.is_empty() {
+            return None;
+        }
+        Some(region)
+    }
+
+    /// Returns the texture paths inside `region`.
+    fn texture_paths(&self, region: &Region, screen: Option<&Screen>) -> Paths {
+        let s = self.spacing;
+        let h = s / 6.0;
+        // The texture is left out where its finest spacing is below a pixel.
+        let finest = match self.texture {
+            GroundTexture::CheckerHatch => h / SQRT_2,
+            _ => s,
+        };
+        let mut texture = region.clone();
+        if let Some(screen) = screen {
+            texture.edges.push(self.footprint_edge(screen, finest));
+        }
+
+        // Each line with the offset to its neighbour.
+        let mut lines: Vec<(Segment, (f64, f64))> = Vec::new();
+        let mut curves: Vec<Vec<(f64, f64)>> = Vec::new();
+        if let Some((u0, v0, u1, v1)) = texture.bounds() {
+            match self.texture {
+                GroundTexture::Grid => {
+                    for i in (u0 / s).ceil() as i64..=(u1 / s).floor() as i64 {
+                        let u = i as f64 * s;
+                        lines.push((((u, v0), (u, v1)), (s, 0.0)));
+                    }
+                    for j in (v0 / s).ceil() as i64..=(v1 / s).floor() as i64 {
+                        let v = j as f64 * s;
+                        lines.push((((u0, v), (u1, v)), (0.0, s)));
+                    }
+                }
+                GroundTexture::CheckerHatch => {
+                    for i in (u0 / s).floor() as i64..(u1 / s).ceil() as i64 {
+                        for j in (v0 / s).floor() as i64..(v1 / s).ceil() as i64 {
+                            if (i + j).rem_euclid(2) != 0 {
+                                continue;
+                            }
+                            // Lines u - v = c across the square
+                            let (cu, cv) = (i as f64 * s, j as f64 * s);
+                            for k in 1..12 {
+                                let c = cu - cv - s + k as f64 * h;
+                                let a = cu.max(cv + c);
+                                let b = (cu + s).min(cv + s + c);
+                                lines.push((((a, a - c), (b, b - c)), (h / 2.0, -h / 2.0)));
+                            }
+                        }
+                    }
+                }
+                GroundTexture::Rings => {
+                    let corners = [(u0, v0), (u1, v0), (u0, v1), (u1, v1)];
+                    let far = corners.iter().map(|(u, v)| u.hypot(*v)).fold(0.0, f64::max);
+                    let near = 0.0_f64.clamp(u0, u1).hypot(0.0_f64.clamp(v0, v1));
+                    for k in ((near / s).ceil() as i64).max(1)..=(far / s).floor() as i64 {
+                        let r = k as f64 * s;
+                        let n = ((2.0 * PI * r / (s / 8.0)).ceil() as usize).max(72);
+                        let circle = (0..=n)
+                            .map(|i| {
+                                let a = 2.0 * PI * i as f64 / n as f64;
+                                (r * a.cos(), r * a.sin())
+                            })
+                            .collect();
+                        curves.push(circle);
+                    }
+                }
+            }
+        }
+
+        let mut result: Vec<Path> = Vec::new();
+        for ((a, b), step) in lines {
+            if let Some((a, b)) = texture.clip_segment(a, b) {
+                for (a, b) in self.resolved(screen, a, b, step) {
+                    result.push(vec![self.to_world(a), self.to_world(b)]);
+                }
+            }
+        }
+        if let Some(e) = self.extent {
+            let border = [
+                ((-e, -e), (e, -e)),
+                ((e, -e), (e, e)),
+                ((e, e), (-e, e)),
+                ((-e, e), (-e, -e)),
+            ];
+            for (a, b) in border {
+                if let Some((a, b)) = region.clip_segment(a, b) {
+                    result.push(vec![self.to_world(a), self.to_world(b)]);
+                }
+            }
+        }
+        for curve in curves {
+            let mut path = Vec::new();
+            for p in curve {
+                let r = p.0.hypot(p.1);
+                let step = (p.0 / r * s, p.1 / r * s);
+                let tangent = (-p.1, p.0);
+                if texture.contains(p) && self.resolves(screen, p, tangent, step) {
+                    path.push(self.to_world(p));
+                } else if !path.is_empty() {
+                    result.push(std::mem::take(&mut path));
+                }
+            }
+            if path.len() > 1 {
+                result.push(path);
+            }
+        }
+        result.retain(|p| p.len() > 1);
+        Paths::from_vec(result)
+    }
+
+    /// Returns the half-plane of plane coordinates where lines `spacing`
+    /// apart may be at least [`MIN_LINE_PIXELS`] apart on `screen`.
+    ///
+    /// A displacement `d` at depth `w` moves by at most
+    /// `|d| * (|Mx| + |Mw|) / w` in normalized device coordinates along x,
+    /// and likewise along y, where `Mx` and `Mw` are the rows of the matrix
+    /// restricted to the plane. Past the depth where this is below the
+    /// limit, no line spacing can be seen.
+    fn footprint_edge(&self, screen: &Screen, spacing: f64) -> [f64; 3] {
+        let (u, w) = self.axes();
+        let m = &screen.matrix;
+        let rx = Vector::new(m.x00, m.x01, m.x02);
+        let ry = Vector::new(m.x10, m.x11, m.x12);
+        let rw = Vector::new(m.x30, m.x31, m.x32);
+        let norm = |r: Vector| r.dot(u).hypot(r.dot(w));
+        let scale = (screen.width / 2.0 * (norm(rx) + norm(rw)))
+            .hypot(screen.height / 2.0 * (norm(ry) + norm(rw)));
+        // The depth at plane coordinates (a, b) is
+        // a * rw.u + b * rw.w + rw.point + m.x33.
+        [
+            rw.dot(u),
+            rw.dot(w),
+            spacing * scale / MIN_LINE_PIXELS - rw.dot(self.point) - m.x33,
+        ]
+    }
+
+    /// Tests if the line through plane coordinates `p` along `direction`
+    /// is at least [`MIN_LINE_PIXELS`] from its neighbour at offset `step`
+    /// on screen.
+    fn resolves(
+        &self,
+        screen: Option<&Screen>,
+        p: (f64, f64),
+        direction: (f64, f64),
+        step: (f64, f64),
+    ) -> bool {
+        let Some(screen) = screen else {
+            return true;
+        };
+        let scale = step.0.hypot(step.1) / direction.0.hypot(direction.1);
+        let along = (p.0 + direction.0 * scale, p.1 + direction.1 * scale);
+        let q = (p.0 + step.0, p.1 + step.1);
+        let [p, along, q] = [p, along, q].map(|x| self.to_world(x));
+        screen.line_distance(p, along, q) >= MIN_LINE_PIXELS
+    }
+
+    /// Returns the parts of the segment from `a` to `b` that are at least
+    /// [`MIN_LINE_PIXELS`] from the neighbouring line at offset `step`.
+    fn resolved(
+        &self,
+        screen: Option<&Screen>,
+        a: (f64, f64),
+        b: (f64, f64),
+        step: (f64, f64),
+    ) -> Vec<Segment> {
+        if screen.is_none() {
+            return vec![(a, b)];
+        }
+        let at = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+        let direction = (b.0 - a.0, b.1 - a.1);
+        let ok = |t: f64| self.resolves(screen, at(t), direction, step);
+
+        let mut result = Vec::new();
+        let mut start = if ok(0.0) { Some(0.0) } else { None };
+        let mut previous = (0.0, start.is_some());
+        for i in 1..=FOOTPRINT_SAMPLES {
+            let t = i as f64 / FOOTPRINT_SAMPLES as f64;
+            let current = ok(t);
+            if current != previous.1 {
+                let (mut lo, mut hi) = (previous.0, t);
+                for _ in 0..FOOTPRINT_REFINE {
+                    let mid = (lo + hi) / 2.0;
+                    if ok(mid) == previous.1 {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                let edge = (lo + hi) / 2.0;
+                if current {
+                    start = Some(edge);
+                } else if let Some(t0) = start.take() {
+                    result.push((at(t0), at(edge)));
+                }
+            }
+            previous = (t, current);
+        }
+        if let Some(t0) = start {
+            result.push((at(t0), b));
+        }
+        result
+    }
+
+    /// Returns the point at plane coordinates `p`.
+    fn to_world(&self, p: (f64, f64)) -> Vector {
+        let (u, w) = self.axes();
+        self.point.add(u.mul_scalar(p.0)).add(w.mul_scalar(p.1))
+    }
+}
+
+impl Shape for GroundPlane {
+    fn bounding_box(&self) -> Box {
+        let e = match self.extent {
+            Some(e) => e,
+            None => {
+                // Keep the box flat along an axis-aligned normal
+                let n = self.normal;
+                let axis = |c: f64, p: f64| {
+                    if c.abs() == 1.0 {
+                        (p, p)
+                    } else {
+                        (-INF, INF)
+                    }
+                };
+                let (x0, x1) = axis(n.x, self.point.x);
+                let (y0, y1) = axis(n.y, self.point.y);
+                let (z0, z1) = axis(n.z, self.point.z);
+                return Box::new(Vector::new(x0, y0, z0), Vector::new(x1, y1, z1));
+            }
+        };
+        let corners: Vec<Vector> = [(-e, -e), (e, -e), (-e, e), (e, e)]
+            .into_iter()
+            .map(|p| self.to_world(p))
+            .collect();
+        Box::for_vectors(&corners)
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        if v.sub(self.point).dot(self.normal).abs() > f {
+            return false;
+        }
+        match self.extent {
+            Some(e) => {
+                let (u, w) = self.to_plane(v);
+                u.abs() <= e + f && w.abs() <= e + f
+            }
+            None => true,
+        }
+    }
+
+    fn intersect(&self, r: Ray) -> Hit {
+        let d = self.normal.dot(r.direction);
+        if d == 0.0 {
+            return Hit::no_hit();
+        }
+        let t = self.normal.dot(self.point.sub(r.origin)) / d;
+        if t <= 1e-6 {
+            return Hit::no_hit();
+        }
+        let p = r.position(t);
+        if let Some(e) = self.extent {
+            let (u, w) = self.to_plane(p);
+            if u.abs() > e || w.abs() > e {
+                return Hit::no_hit();
+            }
+        }
+        Hit::new(t).with_surface(p, self.normal)
+    }
+
+    fn paths(&self) -> Paths {
+        match self.region(None) {
+            Some(region) => self.texture_paths(&region, None),
+            None => Paths::new(),
+        }
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        let screen = Screen::for_view(view);
+        match self.region(view.matrix.as_ref()) {
+            Some(region) => self.texture_paths(&region, screen.as_ref()),
+            None => Paths::new(),
+        }
+    }
+
+    fn section(&self, _plane: &Plane) -> Paths {
+        // A surface without thickness has no cross-section
+        Paths::new()
+    }
+}
+
+/// The projection of a view onto its viewport, used to measure the spacing
+/// of texture lines in pixels.
+struct Screen {
+    matrix: Matrix,
+    width: f64,
+    height: f64,
+}
+
+impl Screen {
+    /// Returns the screen of a view whose matrix and viewport are known.
+    fn for_view(view: &ViewInfo) -> Option<Screen> {
+        let (width, height) = view.viewport?;
+        Some(Screen {
+            matrix: view.matrix?,
+            width,
+            height,
+        })
+    }
+
+    /// Returns the position of `v` on screen in pixels, or `None` if it is
+    /// behind the eye.
+    fn project(&self, v: Vector) -> Option<(f64, f64)> {
+        let (p, w) = self.matrix.mul_position_homogeneous(v);
+        if w <= 0.0 {
+            return None;
+        }
+        Some((p.x / w * self.width / 2.0, p.y / w * self.height / 2.0))
+    }
+
+    /// Returns the distance in pixels on screen from `q` to the line
+    /// through `a` and `b`, or infinity if a point is behind the eye.
+    fn line_distance(&self, a: Vector, b: Vector, q: Vector) -> f64 {
+        let (Some(a), Some(b), Some(q)) = (self.project(a), self.project(b), self.project(q))
+        else {
+            return INF;
+        };
+        let d = (b.0 - a.0, b.1 - a.1);
+        let length = d.0.hypot(d.1);
+        if length == 0.0 {
+            return (q.0 - a.0).hypot(q.1 - a.1);
+        }
+        ((q.0 - a.0) * d.1 - (q.1 - a.1) * d.0).abs() / length
+    }
+}
+
+/// A convex region of the plane, as half-planes `a * u + b * v <= c`.
+#[derive(Debug, Default, Clone)]
+struct Region {
+    edges: Vec<[f64; 3]>,
+}
+
+impl Region {
+    fn contains(&self, p: (f64, f64)) -> bool {
+        self.edges
+            .iter()
+            .all(|e| e[0] * p.0 + e[1] * p.1 - e[2] <= 1e-9 * (1.0 + e[2].abs()))
+    }
+
+    /// Returns the bounding rectangle `(u0, v0, u1, v1)` of the region, or
+    /// `None` if it is empty.
+    fn bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let mut bounds: Option<(f64, f64, f64, f64)> = None;
+        for (i, e) in self.edges.iter().enumerate() {
+            for f in &self.edges[i + 1..] {
+                let det = e[0] * f[1] - e[1] * f[0];
+                if det.abs() < 1e-12 {
+                    continue;
+                }
+                let p = (
+                    (e[2] * f[1] - e[1] * f[2]) / det,
+                    (e[0] * f[2] - e[2] * f[0]) / det,
+                );
+                if !self.contains(p) {
+                    continue;
+                }
+                bounds = Some(match bounds {
+                    Some((u0, v0, u1, v1)) => (u0.min(p.0), v0.min(p.1), u1.max(p.0), v1.max(p.1)),
+                    None => (p.0, p.1, p.0, p.1),
+                });
+            }
+        }
+        bounds
+    }
+
+    /// Clips the segment from `a` to `b` to the region.
+    fn clip_segment(&self, a: (f64, f64), b: (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+        let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+        for e in &self.edges {
+            let fa = e[0] * a.0 + e[1] * a.1 - e[2];
+            let fb = e[0] * b.0 + e[1] * b.1 - e[2];
+            if fa > 0.0 && fb > 0.0 {
+                return None;
+            }
+            if fa > 0.0 {
+                t0 = t0.max(fa / (fa - fb));
+            } else if fb > 0.0 {
+                t1 = t1.min(fa / (fa - fb));
+            }
+        }
+        if t0 >= t1 {
+            return None;
+        }
+        let at = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+        Some((at(t0), at(t1)))
+    }
+}
@@ -0,0 +1,408 @@
+//! Light-direction hatch shading.
+//!
+//! This module provides the [`Hatching`] settings, which make
+//! [`Scene::render_camera`](crate::Scene::render_camera) add hatch lines to
+//! surfaces that face away from a directional light. Surfaces that are
+//! darker still, or that lie in a cast shadow, are cross-hatched.
+//!
+//! Hatch lines are straight lines in screen space. Each line is sampled by
+//! casting rays from the camera into the scene, and the shade of the
+//! surface hit at each sample decides whether the line is drawn there.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Camera, Cube, Hatching, RenderOptions, Scene, Sphere, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Cube::new(Vector::new(-2.0, -2.0, -1.0), Vector::new(2.0, 2.0, -0.9)));
+//! scene.add(Sphere::new(Vector::new(0.0, 0.0, 0.0), 0.9));
+//!
+//! let camera = Camera::perspective(
+//!     Vector::new(4.0, 3.0, 2.0),
+//!     Vector::new(0.0, 0.0, 0.0),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     50.0,
+//! );
+//! let options = RenderOptions::new()
+//!     .with_hatching(Hatching::new(Vector::new(-1.0, 1.0, 2.0)).with_spacing(5.0));
+//!
+//! let paths = scene.render_camera(&camera, &options);
+//! paths.write_to_svg("output.svg", camera.width, camera.height).unwrap();
+//! ```
+
+use crate::hit::Hit;
+use crate::matrix::Matrix;
+use crate::path::{Path, Paths};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::style::Style;
+use crate::util::radians;
+use crate::vector::Vector;
+use std::sync::Arc;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Number of bisection steps used to locate where a hatch line starts or ends.
+const HATCH_REFINE: usize = 8;
+
+/// Screen-space offset, in pixels, of the neighbouring rays used to estimate
+/// surface normals that hits do not provide.
+const NORMAL_OFFSET: f64 = 0.25;
+
+/// Settings for light-direction hatch shading.
+///
+/// The shade of a surface is the cosine of the angle between its normal and
+/// the direction to the light. Surfaces with a shade below
+/// [`hatch`](Hatching::hatch) get hatch lines, and surfaces below
+/// [`cross_hatch`](Hatching::cross_hatch) or in shadow get a second set of
+/// lines at right angles to the first.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{Hatching, Vector};
+///
+/// let hatching = Hatching::new(Vector::new(1.0, 1.0, 1.0))
+///     .with_spacing(3.0)
+///     .with_angle(30.0)
+///     .with_thresholds(0.6, 0.25);
+/// assert_eq!(hatching.cross_hatch, 0.25);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hatching {
+    /// The direction towards the light.
+    pub light: Vector,
+    /// Distance between hatch lines in output pixels.
+    pub spacing: f64,
+    /// Angle of the hatch lines in degrees, counter-clockwise from the x axis.
+    pub angle: f64,
+    /// Shade below which surfaces are hatched.
+    pub hatch: f64,
+    /// Shade below which surfaces are cross-hatched.
+    pub cross_hatch: f64,
+    /// Whether cast shadows are cross-hatched.
+    pub shadows: bool,
+    /// Distance between samples along a hatch line in output pixels.
+    pub step: f64,
+    /// The style of the hatch lines, if any.
+    pub style: Option<Style>,
+}
+
+impl Hatching {
+    /// Creates hatch settings for a light shining from `light`.
+    ///
+    /// The defaults are lines 4 pixels apart at 45 degrees, hatching below a
+    /// shade of `0.5`, cross-hatching below `0.2`, and shadows enabled.
+    pub fn new(light: Vector) -> Self {
+        Hatching {
+            light,
+            spacing: 4.0,
+            angle: 45.0,
+            hatch: 0.5,
+            cross_hatch: 0.2,
+            shadows: true,
+            step: 1.0,
+            style: None,
+        }
+    }
+
+    /// Sets the distance between hatch lines.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the angle of the hatch lines in degrees.
+    pub fn with_angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    /// Sets the shades below which surfaces are hatched and cross-hatched.
+    pub fn with_thresholds(mut self, hatch: f64, cross_hatch: f64) -> Self {
+        self.hatch = hatch;
+        self.cross_hatch = cross_hatch;
+        self
+    }
+
+    /// Sets whether cast shadows are cross-hatched.
+    pub fn with_shadows(mut self, shadows: bool) -> Self {
+        self.shadows = shadows;
+        self
+    }
+
+    /// Sets the distance between samples along a hatch line.
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Sets the style of the hatch lines.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+/// One hatch line: all points `origin + direction * s` for `s` in `s0..=s1`,
+/// drawn where the shade level is at least `level`.
+struct HatchLine {
+    origin: (f64, f64),
+    direction: (f64, f64),
+    s0: f64,
+    s1: f64,
+    level: u8,
+}
+
+/// Samples the shade of a scene through the screen.
+struct Shader<'a> {
+    scene: &'a Scene,
+    inverse: Matrix,
+    width: f64,
+    height: f64,
+    light: Vector,
+    hatching: &'a Hatching,
+}
+
+impl Shader<'_> {
+    /// Returns the camera ray through a screen position and the distance to
+    /// the far plane along it.
+    fn ray(&self, x: f64, y: f64) -> (Ray, f64) {
+        let ndc_x = x / self.width * 2.0 - 1.0;
+        let ndc_y = y / self.height * 2.0 - 1.0;
+        let unproject = |z: f64| {
+            let (v, w) = self
+                .inverse
+                .mul_position_homogeneous(Vector::new(ndc_x, ndc_y, z));
+            v.div_scalar(w)
+        };
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+        let d = far.sub(near);
+        (Ray::new(near, d.normalize()), d.length())
+    }
+
+    /// Returns the surface hit seen through a screen position, and the ray.
+    fn surface(&self, x: f64, y: f64) -> Option<(Hit, Ray)> {
+        let (r, far) = self.ray(x, y);
+        let hit = self.scene.intersect(r);
+        if !hit.is_ok() || hit.t > far {
+            return None;
+        }
+        Some((hit, r))
+    }
+
+    /// Returns the surface normal at a hit, facing the camera.
+    fn normal(&self, x: f64, y: f64, hit: &Hit, r: Ray) -> Option<Vector> {
+        let n = if hit.normal == Vector::default() {
+            self.estimate_normal(x, y, r.position(hit.t), r)?
+        } else {
+            hit.normal
+        };
+        if n.dot(r.direction) > 0.0 {
+            Some(n.mul_scalar(-1.0))
+        } else {
+            Some(n)
+        }
+    }
+
+    /// Estimates the surface normal at `p` from the surface points seen
+    /// through neighbouring screen positions, for shapes whose hits carry
+    /// no normal.
+    fn estimate_normal(&self, x: f64, y: f64, p: Vector, r: Ray) -> Option<Vector> {
+        let neighbour = |dx: f64, dy: f64| {
+            [1.0, -1.0].into_iter().find_map(|sign| {
+                let (hit, ray) = self.surface(x + dx * sign, y + dy * sign)?;
+                let q = ray.position(hit.t);
+                let d = q.sub(p).mul_scalar(sign);
+                // Reject neighbours on another surface behind or in front.
+                if d.length() > p.sub(r.origin).length() * 0.01 {
+                    return None;
+                }
+                Some(d)
+            })
+        };
+        let du = neighbour(NORMAL_OFFSET, 0.0)?;
+        let dv = neighbour(0.0, NORMAL_OFFSET)?;
+        let n = du.cross(dv);
+        if n.length() == 0.0 {
+            return None;
+        }
+        Some(n.normalize())
+    }
+
+    /// Returns 0 for no hatching, 1 for hatching and 2 for cross-hatching.
+    fn level(&self, x: f64, y: f64) -> u8 {
+        let Some((hit, r)) = self.surface(x, y) else {
+            return 0;
+        };
+        let Some(n) = self.normal(x, y, &hit, r) else {
+            return 0;
+        };
+        let p = r.position(hit.t);
+        let shade = n.dot(self.light);
+        if shade < self.hatching.cross_hatch {
+            return 2;
+        }
+        if self.hatching.shadows && shade > 0.0 {
+            let shadow = Ray::new(p.add(n.mul_scalar(0.01)), self.light);
+            if self.scene.intersect(shadow).is_ok() {
+                return 2;
+            }
+        }
+        if shade < self.hatching.hatch {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Returns the segments of a hatch line that are drawn.
+    fn trace(&self, line: &HatchLine) -> Vec<Path> {
+        let at = |s: f64| {
+            (
+                line.origin.0 + line.direction.0 * s,
+                line.origin.1 + line.direction.1 * s,
+            )
+        };
+        let inside = |s: f64| {
+            let (x, y) = at(s);
+            self.level(x, y) >= line.level
+        };
+        let point = |s: f64| {
+            let (x, y) = at(s);
+            Vector::new(x, y, 0.0)
+        };
+        // Locates the transition between two samples.
+        let boundary = |mut lo: f64, mut hi: f64, lo_inside: bool| {
+            for _ in 0..HATCH_REFINE {
+                let mid = (lo + hi) / 2.0;
+                if inside(mid) == lo_inside {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            (lo + hi) / 2.0
+        };
+
+        let mut result = Vec::new();
+        let n = ((line.s1 - line.s0) / self.hatching.step).ceil().max(1.0) as usize;
+        let mut start = None;
+        let mut previous = (line.s0, false);
+        for i in 0..=n {
+            let s = (line.s0 + i as f64 * self.hatching.step).min(line.s1);
+            let value = inside(s);
+            if i == 0 {
+                if value {
+                    start = Some(s);
+                }
+            } else if value != previous.1 {
+                let b = boundary(previous.0, s, previous.1);
+                match start.take() {
+                    Some(s0) => result.push(vec![point(s0), point(b)]),
+                    None => start = Some(b),
+                }
+            }
+            previous = (s, value);
+        }
+        if let Some(s0) = start {
+            result.push(vec![point(s0), point(line.s1)]);
+        }
+        result
+    }
+}
+
+/// Returns the hatch lines of the scene as seen through `matrix`, in
+/// output coordinates.
+pub(crate) fn hatch(
+    scene: &Scene,
+    matrix: &Matrix,
+    width: f64,
+    height: f64,
+    hatching: &Hatching,
+) -> Paths {
+    if hatching.spacing <= 0.0 || hatching.step <= 0.0 {
+        return Paths::new();
+    }
+    let shader = Shader {
+        scene,
+        inverse: matrix.inverse(),
+        width,
+        height,
+        light: hatching.light.normalize(),
+        hatching,
+    };
+
+    let mut lines = Vec::new();
+    for (level, angle) in [(1, hatching.angle), (2, hatching.angle + 90.0)] {
+        let a = radians(angle);
+        let direction = (a.cos(), a.sin());
+        let normal = (-a.sin(), a.cos());
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)];
+        let offsets = corners.map(|(x, y)| x * normal.0 + y * normal.1);
+        let min = offsets.iter().cloned().fold(f64::MAX, f64::min);
+        let max = offsets.iter().cloned().fold(f64::MIN, f64::max);
+
+        let mut k = (min / hatching.spacing).ceil();
+        while k * hatching.spacing <= max {
+            let offset = k * hatching.spacing;
+            let origin = (normal.0 * offset, normal.1 * offset);
+            if let Some((s0, s1)) = clip_line(origin, direction, width, height) {
+                lines.push(HatchLine {
+                    origin,
+                    direction,
+                    s0,
+                    s1,
+                    level,
+                });
+            }
+            k += 1.0;
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    let segments: Vec<Vec<Path>> = lines.par_iter().map(|l| shader.trace(l)).collect();
+    #[cfg(not(feature = "parallel"))]
+    let segments: Vec<Vec<Path>> = lines.iter().map(|l| shader.trace(l)).collect();
+
+    let paths = Paths::from_vec(segments.into_iter().flatten().collect());
+    match &hatching.style {
+        Some(style) => paths.with_style_arc(Arc::new(style.clone())),
+        None => paths,
+    }
+}
+
+/// Returns the range of `s` for which `origin + direction * s` lies inside
+/// the viewport.
+fn clip_line(
+    origin: (f64, f64),
+    direction: (f64, f64),
+    width: f64,
+    height: f64,
+) -> Option<(f64, f64)> {
+    let mut s0 = f64::MIN;
+    let mut s1 = f64::MAX;
+    for (o, d, size) in [
+        (origin.0, direction.0, width),
+        (origin.1, direction.1, height),
+    ] {
+        if d.abs() < 1e-12 {
+            if o < 0.0 || o > size {
+                return None;
+            }
+            continue;
+        }
+        let a = (0.0 - o) / d;
+        let b = (size - o) / d;
+        s0 = s0.max(a.min(b));
+        s1 = s1.min(a.max(b));
+    }
+    if s1 > s0 {
+        Some((s0, s1))
+    } else {
+        None
+    }
+}
@@ -1,18 +1,76 @@
 use crate::common::INF;
+use crate::vector::Vector;
 
+/// The result of a ray-shape intersection test.
+///
+/// Besides the distance `t` along the ray, a hit describes the surface that
+/// was hit: the hit position, the outward surface normal there, and which
+/// shape it belongs to.
 #[derive(Debug, Clone, Copy)]
 pub struct Hit {
     pub t: f64,
     pub ok: bool,
+    /// The point where the ray meets the surface.
+    ///
+    /// [`Tree::intersect`](crate::Tree::intersect) and
+    /// [`Scene::intersect`](crate::Scene::intersect) always set this to the
+    /// ray's position at `t`. Hits returned by a shape directly may leave it
+    /// as the zero vector.
+    pub position: Vector,
+    /// The outward unit normal of the surface at `position`.
+    ///
+    /// This is the zero vector for hits from shapes that do not provide
+    /// normals.
+    pub normal: Vector,
+    /// The index of the shape that was hit, in the list the enclosing
+    /// [`Tree`](crate::Tree) was built from.
+    ///
+    /// For hits returned by [`Scene::intersect`](crate::Scene::intersect)
+    /// this is the index into [`Scene::shapes`](crate::Scene::shapes).
+    pub shape: Option<usize>,
 }
 
 impl Hit {
     pub fn new(t: f64) -> Self {
-        Hit { t, ok: true }
+        Hit {
+            t,
+            ok: true,
+            position: Vector::default(),
+            normal: Vector::default(),
+            shape: None,
+        }
     }
 
     pub fn no_hit() -> Self {
-        Hit { t: INF, ok: false }
+        Hit {
+            t: INF,
+            ok: false,
+            position: Vector::default(),
+            normal: Vector::default(),
+            shape: None,
+        }
+    }
+
+    /// Sets the hit position and surface normal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Hit, Vector};
+    ///
+    /// let hit = Hit::new(2.0).with_surface(Vector::new(0.0, 0.0, 1.0), Vector::new(0.0, 0.0, 1.0));
+    /// assert_eq!(hit.normal.z, 1.0);
+    /// ```
+    pub fn with_surface(mut self, position: Vector, normal: Vector) -> Self {
+        self.position = position;
+        self.normal = normal;
+        self
+    }
+
+    /// Sets the index of the shape that was hit.
+    pub fn with_shape(mut self, shape: usize) -> Self {
+        self.shape = Some(shape);
+        self
     }
 
     pub fn is_ok(&self) -> bool {
@@ -2,6 +2,7 @@
 
 pub mod axis;
 pub mod bounding_box;
+pub mod camera;
 pub mod common;
 pub mod cone;
 pub mod csg;
@@ -9,17 +10,26 @@ pub mod cube;
 pub mod cylinder;
 pub mod filter;
 pub mod function;
+pub mod gcode;
+pub mod ground;
+pub mod hatch;
 pub mod hit;
 pub mod matrix;
 pub mod mesh;
 pub mod obj;
+pub mod parametric;
 pub mod path;
 pub mod plane;
+pub mod plot;
+pub mod print;
 pub mod ray;
 pub mod scene;
+pub mod sdf;
 pub mod shape;
 pub mod sphere;
 pub mod stl;
+pub mod style;
+pub mod torus;
 pub mod tree;
 pub mod triangle;
 pub mod util;
@@ -28,25 +38,39 @@ pub mod vector;
 // Re-exports for convenient access
 pub use axis::Axis;
 pub use bounding_box::Box;
-pub use cone::{new_transformed_cone, new_transformed_outline_cone, Cone, OutlineCone};
-pub use csg::{new_difference, new_intersection, BooleanShape, Op};
+pub use camera::{Camera, Projection, ViewInfo};
+pub use cone::{
+    new_transformed_cone, new_transformed_outline_cone, new_transformed_outline_cone_from_camera,
+    new_transformed_truncated_cone, Cone, OutlineCone, TruncatedCone,
+};
+pub use csg::{new_difference, new_intersection, new_union, BooleanShape, Op};
 pub use cube::{Cube, CubeTexture};
 pub use cylinder::{
-    new_transformed_cylinder, new_transformed_outline_cylinder, Cylinder, OutlineCylinder,
+    new_transformed_cylinder, new_transformed_outline_cylinder,
+    new_transformed_outline_cylinder_from_camera, CapTexture, Cylinder, OutlineCylinder,
 };
-pub use filter::{ClipFilter, Filter};
+pub use filter::{ClipFilter, Filter, ViewFilter};
 pub use function::{Direction, Function, FunctionTexture};
+pub use gcode::{GcodeOptions, PenLift};
+pub use ground::{GroundPlane, GroundTexture};
+pub use hatch::Hatching;
 pub use hit::Hit;
 pub use matrix::Matrix;
-pub use mesh::Mesh;
+pub use mesh::{Mesh, MeshEdges, WatertightReport};
 pub use obj::load_obj;
+pub use parametric::ParametricSurface;
 pub use path::{Path, Paths};
-pub use plane::Plane;
+pub use plane::{Plane, DEFAULT_SECTION_RESOLUTION};
+pub use plot::OrderReport;
+pub use print::{LineCap, PageOptions};
 pub use ray::Ray;
-pub use scene::Scene;
+pub use scene::{Annotation, RenderOptions, Scene, StepUnits, Visibility, MIN_BISECT_TOLERANCE};
+pub use sdf::{SdfShape, SdfTexture};
 pub use shape::{EmptyShape, Shape, TransformedShape};
 pub use sphere::{lat_lng_to_xyz, OutlineSphere, Sphere, SphereTexture};
 pub use stl::{load_binary_stl, load_stl, save_binary_stl};
+pub use style::Style;
+pub use torus::{new_transformed_torus, OutlineTorus, Torus, TorusTexture};
 pub use tree::Tree;
 pub use triangle::Triangle;
 pub use util::{degrees, median, radians};
@@ -47,7 +47,7 @@ use crate::vector::Vector;
 /// let transformed = transform.mul_position(point);
 /// assert!((transformed.x - 1.0).abs() < 1e-10);
 /// ```
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct Matrix {
     pub x00: f64,
     pub x01: f64,
@@ -353,6 +353,17 @@ impl Matrix {
         Vector::new(x / w, y / w, z / w)
     }
 
+    /// Transforms a position without the perspective divide.
+    ///
+    /// Returns the homogeneous `(x, y, z)` coordinates and `w` separately.
+    pub fn mul_position_homogeneous(&self, b: Vector) -> (Vector, f64) {
+        let x = self.x00 * b.x + self.x01 * b.y + self.x02 * b.z + self.x03;
+        let y = self.x10 * b.x + self.x11 * b.y + self.x12 * b.z + self.x13;
+        let z = self.x20 * b.x + self.x21 * b.y + self.x22 * b.z + self.x23;
+        let w = self.x30 * b.x + self.x31 * b.y + self.x32 * b.z + self.x33;
+        (Vector::new(x, y, z), w)
+    }
+
     /// Transforms a direction vector by this matrix.
     ///
     /// Unlike `mul_position`, this ignores the translation component
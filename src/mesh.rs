@@ -1,4 +1,5 @@
 use crate::bounding_box::Box;
+use crate::camera::ViewInfo;
 use crate::cube::Cube;
 use crate::hit::Hit;
 use crate::matrix::Matrix;
@@ -8,14 +9,79 @@ use crate::ray::Ray;
 use crate::shape::Shape;
 use crate::tree::Tree;
 use crate::triangle::Triangle;
+use crate::util::degrees;
 use crate::vector::Vector;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+/// Which edges of a [`Mesh`] are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MeshEdges {
+    /// All three edges of every triangle (default).
+    #[default]
+    All,
+    /// Only the edges that define the shape of the mesh.
+    ///
+    /// Each edge is drawn once. An edge is kept if it is a boundary edge
+    /// (used by one triangle), a non-manifold edge (used by more than two),
+    /// a crease whose dihedral angle exceeds `crease_angle` degrees, or, if
+    /// `eye` is set, a silhouette edge between a triangle facing the eye and
+    /// one facing away. Silhouettes assume consistent triangle winding.
+    ///
+    /// When a [`Scene`](crate::Scene) is rendered, silhouettes are found
+    /// from the render camera instead of `eye` (see
+    /// [`Shape::paths_for_view`]), so any `Some` value enables them.
+    Feature {
+        /// Minimum angle between adjacent face normals, in degrees.
+        crease_angle: f64,
+        /// Eye position, in mesh coordinates, for silhouette edges.
+        eye: Option<Vector>,
+    },
+}
+
+/// How far a [`Mesh`] is from being a closed solid, as found by
+/// [`Mesh::check_watertight`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WatertightReport {
+    /// Edges used by a single triangle, where the surface has a hole.
+    pub boundary_edges: usize,
+    /// Edges shared by more than two triangles.
+    pub non_manifold_edges: usize,
+}
+
+impl WatertightReport {
+    /// Returns `true` if every edge is shared by exactly two triangles.
+    pub fn is_watertight(&self) -> bool {
+        self.boundary_edges == 0 && self.non_manifold_edges == 0
+    }
+}
+
+/// Directions of the rays cast by [`Mesh::contains`]. They are far from the
+/// axes so that they rarely graze the edges of axis-aligned models.
+const PARITY_DIRECTIONS: [Vector; 3] = [
+    Vector {
+        x: 0.5701,
+        y: 0.6114,
+        z: 0.5488,
+    },
+    Vector {
+        x: -0.6479,
+        y: 0.3347,
+        z: 0.6843,
+    },
+    Vector {
+        x: 0.2917,
+        y: -0.7232,
+        z: 0.6260,
+    },
+];
+
 pub struct Mesh {
     pub bx: Box,
     pub triangles: Vec<Triangle>,
+    pub edges: MeshEdges,
     tree: Option<Arc<Tree>>,
+    watertight: Option<bool>,
 }
 
 impl Mesh {
@@ -24,8 +90,173 @@ impl Mesh {
         Mesh {
             bx,
             triangles,
+            edges: MeshEdges::default(),
             tree: None,
+            watertight: None,
+        }
+    }
+
+    /// Sets which edges are drawn.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Mesh, MeshEdges, Shape, Triangle, Vector};
+    ///
+    /// // Two coplanar triangles forming a square.
+    /// let (a, b, c, d) = (
+    ///     Vector::new(0.0, 0.0, 0.0),
+    ///     Vector::new(1.0, 0.0, 0.0),
+    ///     Vector::new(1.0, 1.0, 0.0),
+    ///     Vector::new(0.0, 1.0, 0.0),
+    /// );
+    /// let mesh = Mesh::new(vec![Triangle::new(a, b, c), Triangle::new(a, c, d)])
+    ///     .with_edges(MeshEdges::Feature { crease_angle: 30.0, eye: None });
+    ///
+    /// // The shared diagonal is dropped, leaving the four sides.
+    /// assert_eq!(mesh.paths().paths.len(), 4);
+    /// ```
+    pub fn with_edges(mut self, edges: MeshEdges) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    /// Returns the edges of the mesh, each mapped to the indices of the
+    /// triangles that use it.
+    ///
+    /// Vertices are matched by exact position.
+    pub fn edge_adjacency(&self) -> HashMap<(Vector, Vector), Vec<usize>> {
+        let mut edges: HashMap<(Vector, Vector), Vec<usize>> = HashMap::new();
+        for (i, t) in self.triangles.iter().enumerate() {
+            for (a, b) in [(t.v1, t.v2), (t.v2, t.v3), (t.v3, t.v1)] {
+                edges.entry(edge_key(a, b)).or_default().push(i);
+            }
+        }
+        edges
+    }
+
+    /// Checks that the mesh is a closed surface, which
+    /// [`contains`](Shape::contains) needs to tell inside from outside.
+    ///
+    /// Vertices are matched by exact position, so a mesh whose triangles
+    /// do not share vertices (as in some STL exports) is reported as open.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Mesh, Triangle, Vector};
+    ///
+    /// let (a, b, c, d) = (
+    ///     Vector::new(0.0, 0.0, 0.0),
+    ///     Vector::new(1.0, 0.0, 0.0),
+    ///     Vector::new(0.0, 1.0, 0.0),
+    ///     Vector::new(0.0, 0.0, 1.0),
+    /// );
+    /// let faces = vec![
+    ///     Triangle::new(a, c, b),
+    ///     Triangle::new(a, b, d),
+    ///     Triangle::new(a, d, c),
+    ///     Triangle::new(b, c, d),
+    /// ];
+    /// assert!(Mesh::new(faces.clone()).check_watertight().is_watertight());
+    ///
+    /// // Dropping a face leaves three edges with a single triangle.
+    /// let report = Mesh::new(faces[..3].to_vec()).check_watertight();
+    /// assert_eq!(report.boundary_edges, 3);
+    /// ```
+    pub fn check_watertight(&self) -> WatertightReport {
+        let mut report = WatertightReport::default();
+        for faces in self.edge_adjacency().values() {
+            match faces.len() {
+                1 => report.boundary_edges += 1,
+                2 => {}
+                _ => report.non_manifold_edges += 1,
+            }
+        }
+        report
+    }
+
+    /// Returns `true` if the mesh is a closed surface.
+    ///
+    /// The result is cached when the mesh is compiled, along with its
+    /// bounding volume hierarchy. Until then it is checked on every call, so
+    /// that edits to `triangles` are always seen.
+    pub fn is_watertight(&self) -> bool {
+        self.watertight
+            .unwrap_or_else(|| self.check_watertight().is_watertight())
+    }
+
+    /// Tests if `v` is inside the mesh by counting the surface crossings of
+    /// rays cast from it. The majority of three rays decides, in case one
+    /// passes through an edge or a vertex.
+    fn parity_contains(&self, v: Vector) -> bool {
+        let eps = self.bx.size().length() * 1e-9;
+        let inside = PARITY_DIRECTIONS
+            .iter()
+            .filter(|&&direction| {
+                let mut crossings = 0;
+                match &self.tree {
+                    Some(tree) => {
+                        let mut origin = v;
+                        loop {
+                            let hit = tree.intersect(Ray::new(origin, direction));
+                            if !hit.is_ok() {
+                                break;
+                            }
+                            crossings += 1;
+                            origin = origin.add(direction.mul_scalar(hit.t + eps));
+                        }
+                    }
+                    None => {
+                        let r = Ray::new(v, direction);
+                        crossings = self
+                            .triangles
+                            .iter()
+                            .filter(|t| t.intersect(r).is_ok())
+                            .count();
+                    }
+                }
+                crossings % 2 == 1
+            })
+            .count();
+        inside >= 2
+    }
+
+    fn paths_feature(&self, crease_angle: f64, eye: Option<Vector>) -> Paths {
+        let normals: Vec<Vector> = self.triangles.iter().map(|t| t.normal()).collect();
+        let facing: Vec<bool> = match eye {
+            Some(eye) => self
+                .triangles
+                .iter()
+                .zip(&normals)
+                .map(|(t, n)| n.dot(eye.sub(t.centroid())) > 0.0)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let adjacency = self.edge_adjacency();
+        let mut result = Vec::new();
+        for (i, t) in self.triangles.iter().enumerate() {
+            for (a, b) in [(t.v1, t.v2), (t.v2, t.v3), (t.v3, t.v1)] {
+                let faces = &adjacency[&edge_key(a, b)];
+                // Emit each edge once, from the first triangle that uses it.
+                if faces[0] != i {
+                    continue;
+                }
+                let keep = match faces.as_slice() {
+                    [f1, f2] => {
+                        let cos = normals[*f1].dot(normals[*f2]).clamp(-1.0, 1.0);
+                        degrees(cos.acos()) > crease_angle
+                            || (eye.is_some() && facing[*f1] != facing[*f2])
+                    }
+                    _ => true,
+                };
+                if keep {
+                    result.push(vec![a, b]);
+                }
+            }
         }
+        Paths::from_vec(result)
     }
 
     pub fn update_bounding_box(&mut self) {
@@ -64,6 +295,7 @@ impl Mesh {
         }
         self.update_bounding_box();
         self.tree = None;
+        self.watertight = None;
     }
 
     pub fn voxelize(&self, size: f64) -> Vec<Cube> {
@@ -105,14 +337,38 @@ impl Shape for Mesh {
                 .collect();
             self.tree = Some(Arc::new(Tree::new(shapes)));
         }
+        if self.watertight.is_none() {
+            self.watertight = Some(self.check_watertight().is_watertight());
+        }
     }
 
     fn bounding_box(&self) -> Box {
         self.bx
     }
 
-    fn contains(&self, _v: Vector, _f: f64) -> bool {
-        false
+    /// Tests if `v` is inside the mesh.
+    ///
+    /// Points within `f` of the surface are inside when `f` is positive and
+    /// outside when it is negative. Meshes that are not watertight (see
+    /// [`Mesh::check_watertight`]) have no inside, and contain no points.
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        if !self.is_watertight() {
+            return false;
+        }
+        let m = f.max(0.0);
+        if !Box::new(self.bx.min.sub_scalar(m), self.bx.max.add_scalar(m)).contains(v) {
+            return false;
+        }
+        if f != 0.0 {
+            let near = match &self.tree {
+                Some(tree) => tree.contains(v, f.abs()),
+                None => self.triangles.iter().any(|t| t.contains(v, f.abs())),
+            };
+            if near {
+                return f > 0.0;
+            }
+        }
+        self.parity_contains(v)
     }
 
     fn intersect(&self, r: Ray) -> Hit {
@@ -122,10 +378,41 @@ impl Shape for Mesh {
     }
 
     fn paths(&self) -> Paths {
-        let mut result = Paths::new();
-        for t in &self.triangles {
-            result.extend(t.paths());
+        match self.edges {
+            MeshEdges::All => {
+                let mut result = Paths::new();
+                for t in &self.triangles {
+                    result.extend(t.paths());
+                }
+                result
+            }
+            MeshEdges::Feature { crease_angle, eye } => self.paths_feature(crease_angle, eye),
+        }
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        match self.edges {
+            MeshEdges::Feature {
+                crease_angle,
+                eye: Some(_),
+            } => self.paths_feature(crease_angle, Some(view.eye)),
+            _ => self.paths(),
         }
-        result
+    }
+
+    fn section(&self, plane: &Plane) -> Paths {
+        plane.intersect_mesh(self)
+    }
+}
+
+/// Orders the endpoints of an edge so both directions map to the same key.
+fn edge_key(a: Vector, b: Vector) -> (Vector, Vector) {
+    // Adding 0.0 turns -0.0 into 0.0, so equal positions hash the same.
+    let a = a.add_scalar(0.0);
+    let b = b.add_scalar(0.0);
+    if (a.x, a.y, a.z) <= (b.x, b.y, b.z) {
+        (a, b)
+    } else {
+        (b, a)
     }
 }
@@ -0,0 +1,245 @@
+//! Parametric surfaces.
+//!
+//! This module provides the [`ParametricSurface`] shape, a surface given by a
+//! function from a rectangle of parameters `(u, v)` to points in space. It is
+//! drawn with isoparametric lines, the curves of constant `u` and of constant
+//! `v`, and hides what lies behind it through a tessellation built from the
+//! same samples.
+//!
+//! # Example
+//!
+//! ```
+//! use larnt::{ParametricSurface, Scene, Vector};
+//! use std::f64::consts::PI;
+//!
+//! // A Möbius strip
+//! let strip = ParametricSurface::new(
+//!     |u: f64, v: f64| {
+//!         let r = 1.0 + v / 2.0 * (u / 2.0).cos();
+//!         Vector::new(r * u.cos(), r * u.sin(), v / 2.0 * (u / 2.0).sin())
+//!     },
+//!     (0.0, 2.0 * PI),
+//!     (-1.0, 1.0),
+//! )
+//! .with_lines(48, 5);
+//!
+//! let mut scene = Scene::new();
+//! scene.add(strip);
+//! ```
+
+use crate::bounding_box::Box;
+use crate::hit::Hit;
+use crate::mesh::Mesh;
+use crate::path::Paths;
+use crate::plane::Plane;
+use crate::ray::Ray;
+use crate::shape::Shape;
+use crate::triangle::Triangle;
+use crate::vector::Vector;
+
+/// A surface given by a function of two parameters.
+///
+/// The function maps each `(u, v)` in `u_range` x `v_range` to a point.
+/// The paths are `u_lines` isolines of constant `u` and `v_lines` isolines
+/// of constant `v`, evenly spaced and including both ends of each range.
+///
+/// The surface is sampled when it is built, so its settings can only be
+/// changed through the `with_*` methods, which sample it again.
+///
+/// For occlusion, the surface is tessellated into triangles on a grid that
+/// has `resolution` cells between neighbouring isolines, so the isolines
+/// run along the edges of the tessellation. Raise the resolution for
+/// strongly curved surfaces. Closed surfaces whose tessellation is
+/// watertight also have an inside (see [`Mesh::contains`](crate::Mesh)).
+///
+/// # Example
+///
+/// ```
+/// use larnt::{ParametricSurface, Ray, Shape, Vector};
+/// use std::f64::consts::PI;
+///
+/// // A helicoid
+/// let mut helicoid = ParametricSurface::new(
+///     |u: f64, v: f64| Vector::new(v * u.cos(), v * u.sin(), u / 4.0),
+///     (0.0, 4.0 * PI),
+///     (-1.0, 1.0),
+/// );
+/// helicoid.compile();
+///
+/// assert_eq!(helicoid.paths().paths.len(), 32);
+/// assert_eq!(helicoid.resolution(), 4);
+/// // The highest turn above (0, 0.5) is at u = 7π/2
+/// let hit = helicoid.intersect(Ray::new(Vector::new(0.0, 0.5, 5.0), Vector::new(0.0, 0.0, -1.0)));
+/// assert!((hit.t - (5.0 - 7.0 * PI / 8.0)).abs() < 1e-2);
+/// ```
+pub struct ParametricSurface<F>
+where
+    F: Fn(f64, f64) -> Vector + Send + Sync,
+{
+    /// The function mapping `(u, v)` to a point on the surface.
+    func: F,
+    /// The range of the `u` parameter.
+    u_range: (f64, f64),
+    /// The range of the `v` parameter.
+    v_range: (f64, f64),
+    /// The number of isolines of constant `u`.
+    u_lines: usize,
+    /// The number of isolines of constant `v`.
+    v_lines: usize,
+    /// The number of tessellation cells between neighbouring isolines.
+    resolution: usize,
+    /// The sampled points, indexed by `u` then `v`.
+    grid: Vec<Vec<Vector>>,
+    mesh: Mesh,
+}
+
+impl<F> ParametricSurface<F>
+where
+    F: Fn(f64, f64) -> Vector + Send + Sync,
+{
+    /// Creates a surface from `func` over the given parameter ranges, with
+    /// 16 isolines in each direction.
+    pub fn new(func: F, u_range: (f64, f64), v_range: (f64, f64)) -> Self {
+        let mut surface = ParametricSurface {
+            func,
+            u_range,
+            v_range,
+            u_lines: 16,
+            v_lines: 16,
+            resolution: 4,
+            grid: Vec::new(),
+            mesh: Mesh::new(Vec::new()),
+        };
+        surface.tessellate();
+        surface
+    }
+
+    /// Sets the number of isolines of constant `u` and of constant `v`.
+    pub fn with_lines(mut self, u_lines: usize, v_lines: usize) -> Self {
+        self.u_lines = u_lines;
+        self.v_lines = v_lines;
+        self.tessellate();
+        self
+    }
+
+    /// Sets the number of tessellation cells between neighbouring isolines.
+    pub fn with_resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution.max(1);
+        self.tessellate();
+        self
+    }
+
+    /// Returns the function mapping `(u, v)` to a point on the surface.
+    pub fn func(&self) -> &F {
+        &self.func
+    }
+
+    /// Returns the range of the `u` parameter.
+    pub fn u_range(&self) -> (f64, f64) {
+        self.u_range
+    }
+
+    /// Returns the range of the `v` parameter.
+    pub fn v_range(&self) -> (f64, f64) {
+        self.v_range
+    }
+
+    /// Returns the number of isolines of constant `u`.
+    pub fn u_lines(&self) -> usize {
+        self.u_lines
+    }
+
+    /// Returns the number of isolines of constant `v`.
+    pub fn v_lines(&self) -> usize {
+        self.v_lines
+    }
+
+    /// Returns the number of tessellation cells between neighbouring
+    /// isolines.
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    /// Returns the number of grid cells along a parameter with `lines`
+    /// isolines.
+    fn cells(&self, lines: usize) -> usize {
+        lines.saturating_sub(1).max(1) * self.resolution
+    }
+
+    /// Samples the grid and rebuilds the tessellation.
+    fn tessellate(&mut self) {
+        let nu = self.cells(self.u_lines);
+        let nv = self.cells(self.v_lines);
+        let (u0, u1) = self.u_range;
+        let (v0, v1) = self.v_range;
+        self.grid = (0..=nu)
+            .map(|i| {
+                let u = u0 + (u1 - u0) * i as f64 / nu as f64;
+                (0..=nv)
+                    .map(|j| (self.func)(u, v0 + (v1 - v0) * j as f64 / nv as f64))
+                    .collect()
+            })
+            .collect();
+
+        let mut triangles = Vec::new();
+        for i in 0..nu {
+            for j in 0..nv {
+                let a = self.grid[i][j];
+                let b = self.grid[i + 1][j];
+                let c = self.grid[i + 1][j + 1];
+                let d = self.grid[i][j + 1];
+                for (p, q, r) in [(a, b, c), (a, c, d)] {
+                    // Skip the slivers left where the surface pinches to a point
+                    if q.sub(p).cross(r.sub(p)).length_squared() > 0.0 {
+                        triangles.push(Triangle::new(p, q, r));
+                    }
+                }
+            }
+        }
+        self.mesh = Mesh::new(triangles);
+    }
+}
+
+impl<F> Shape for ParametricSurface<F>
+where
+    F: Fn(f64, f64) -> Vector + Send + Sync,
+{
+    fn compile(&mut self) {
+        self.mesh.compile();
+    }
+
+    fn bounding_box(&self) -> Box {
+        self.mesh.bounding_box()
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        self.mesh.contains(v, f)
+    }
+
+    fn intersect(&self, r: Ray) -> Hit {
+        self.mesh.intersect(r)
+    }
+
+    fn paths(&self) -> Paths {
+        let mut paths = Vec::new();
+        let step = self.resolution;
+        let nu = self.cells(self.u_lines);
+        let nv = self.cells(self.v_lines);
+
+        // Lines along constant u
+        for i in (0..=nu).step_by(step).take(self.u_lines) {
+            paths.push(self.grid[i].clone());
+        }
+
+        // Lines along constant v
+        for j in (0..=nv).step_by(step).take(self.v_lines) {
+            paths.push(self.grid.iter().map(|row| row[j]).collect());
+        }
+
+        Paths::from_vec(paths)
+    }
+
+    fn section(&self, plane: &Plane) -> Paths {
+        self.mesh.section(plane)
+    }
+}
@@ -1,7 +1,7 @@
 //! Path handling and output.
 //!
 //! This module provides types for working with 2D/3D paths and outputting
-//! them to various formats like PNG and SVG.
+//! them to various formats like PNG, SVG, PDF, EPS, G-code and HPGL.
 //!
 //! # Types
 //!
@@ -30,10 +30,18 @@
 
 use crate::bounding_box::Box;
 use crate::filter::Filter;
+use crate::gcode::{self, GcodeOptions};
 use crate::matrix::Matrix;
+use crate::plot::{self, OrderReport};
+use crate::print::{self, PageOptions};
+use crate::style::Style;
 use crate::vector::Vector;
 use image::{ImageBuffer, Rgb};
 use std::io::Write;
+use std::sync::Arc;
+
+/// HPGL plotter units per millimetre.
+const HPGL_UNITS_PER_MM: f64 = 40.0;
 
 /// A single path represented as a sequence of 3D points.
 pub type Path = Vec<Vector>;
@@ -54,21 +62,36 @@ pub type Path = Vec<Vector>;
 ///     vec![Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)],
 /// ]);
 /// ```
+///
+/// # Styles
+///
+/// Each path can carry a [`Style`], added with [`Paths::push_styled`] or
+/// [`Paths::with_style`] and read back with [`Paths::style`]. Paths pushed
+/// directly onto `paths` are unstyled. Styles are preserved by chopping,
+/// filtering, simplifying and transforming.
 #[derive(Debug, Clone, Default)]
 pub struct Paths {
     /// The collection of paths.
     pub paths: Vec<Path>,
+    /// The style of each path, by index. May be shorter than `paths`.
+    styles: Vec<Option<Arc<Style>>>,
 }
 
 impl Paths {
     /// Creates a new empty `Paths` collection.
     pub fn new() -> Self {
-        Paths { paths: Vec::new() }
+        Paths {
+            paths: Vec::new(),
+            styles: Vec::new(),
+        }
     }
 
     /// Creates a `Paths` collection from a vector of paths.
     pub fn from_vec(paths: Vec<Path>) -> Self {
-        Paths { paths }
+        Paths {
+            paths,
+            styles: Vec::new(),
+        }
     }
 
     /// Adds a path to this collection.
@@ -76,11 +99,55 @@ impl Paths {
         self.paths.push(path);
     }
 
+    /// Adds a path with the given style to this collection.
+    pub fn push_styled(&mut self, path: Path, style: Option<Arc<Style>>) {
+        if style.is_some() || !self.styles.is_empty() {
+            self.styles.resize(self.paths.len(), None);
+            self.styles.push(style);
+        }
+        self.paths.push(path);
+    }
+
     /// Extends this collection with paths from another.
     pub fn extend(&mut self, other: Paths) {
+        if !other.styles.is_empty() {
+            self.styles.resize(self.paths.len(), None);
+            self.styles.extend(other.styles);
+        }
         self.paths.extend(other.paths);
     }
 
+    /// Returns the style of the path at `index`, if it has one.
+    pub fn style(&self, index: usize) -> Option<&Style> {
+        self.styles.get(index).and_then(|s| s.as_deref())
+    }
+
+    /// Returns the shared style of the path at `index`, if it has one.
+    pub fn style_arc(&self, index: usize) -> Option<Arc<Style>> {
+        self.styles.get(index).cloned().flatten()
+    }
+
+    /// Returns these paths with every path set to the given style.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Paths, Style, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![vec![Vector::default(), Vector::new(1.0, 0.0, 0.0)]])
+    ///     .with_style(Style::new().with_color("blue"));
+    /// assert_eq!(paths.style(0).unwrap().color, "blue");
+    /// ```
+    pub fn with_style(self, style: Style) -> Paths {
+        self.with_style_arc(Arc::new(style))
+    }
+
+    /// Returns these paths with every path set to the given shared style.
+    pub fn with_style_arc(mut self, style: Arc<Style>) -> Paths {
+        self.styles = vec![Some(style); self.paths.len()];
+        self
+    }
+
     /// Returns the bounding box of all paths.
     pub fn bounding_box(&self) -> Box {
         if self.paths.is_empty() {
@@ -95,12 +162,7 @@ impl Paths {
 
     /// Applies a transformation matrix to all paths.
     pub fn transform(&self, matrix: &Matrix) -> Paths {
-        let paths = self
-            .paths
-            .iter()
-            .map(|path| path_transform(path, matrix))
-            .collect();
-        Paths { paths }
+        self.map(|path| path_transform(path, matrix))
     }
 
     /// Subdivides paths into smaller segments.
@@ -108,21 +170,189 @@ impl Paths {
     /// This is used internally for visibility testing. The `step` parameter
     /// controls the maximum distance between consecutive points.
     pub fn chop(&self, step: f64) -> Paths {
-        let paths = self
+        self.map(|path| path_chop(path, step))
+    }
+
+    /// Subdivides paths so that consecutive points are at most `step`
+    /// pixels apart on screen.
+    ///
+    /// Each segment is projected with `matrix` (a view-projection matrix
+    /// such as [`Camera::matrix`](crate::Camera::matrix)) onto a viewport of
+    /// `width` x `height` pixels and split in half until its projected
+    /// length is below `step`. Segments that lie entirely outside the view
+    /// volume are left as they are, and segments crossing the near plane
+    /// are cut there, so only the part in front of it is subdivided.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Matrix, Paths, Vector};
+    ///
+    /// let matrix = Matrix::orthographic(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(-1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)]]);
+    ///
+    /// // The segment spans 100 pixels, so a 10 pixel step gives 16 pieces.
+    /// let chopped = paths.chop_projected(&matrix, 100.0, 100.0, 10.0);
+    /// assert_eq!(chopped.paths[0].len(), 17);
+    ///
+    /// // A segment passing behind the camera is cut at the near plane
+    /// // (z = -1 here) and not subdivided past it.
+    /// let matrix = Matrix::perspective(90.0, 1.0, 1.0, 10.0);
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(0.0, 0.0, -2.0), Vector::new(0.0, 0.0, 5.0)]]);
+    /// let chopped = paths.chop_projected(&matrix, 100.0, 100.0, 10.0);
+    /// assert_eq!(chopped.paths[0].len(), 3);
+    /// assert!((chopped.paths[0][1].z + 1.0).abs() < 1e-9);
+    ///
+    /// // Single points are kept.
+    /// let point = Paths::from_vec(vec![vec![Vector::new(0.0, 0.0, -2.0)]]);
+    /// assert_eq!(point.chop_projected(&matrix, 100.0, 100.0, 10.0).paths[0].len(), 1);
+    /// ```
+    pub fn chop_projected(&self, matrix: &Matrix, width: f64, height: f64, step: f64) -> Paths {
+        self.map(|path| path_chop_projected(path, matrix, width, height, step))
+    }
+
+    /// Filters paths using a custom filter.
+    pub fn filter<F: Filter>(&self, f: &F) -> Paths {
+        self.flat_map(|path| path_filter(path, f))
+    }
+
+    /// Filters paths using a custom filter, spreading the work across threads.
+    ///
+    /// Each path is filtered independently on the rayon thread pool and the
+    /// results are concatenated in the original order, so the output is
+    /// identical to [`Paths::filter`].
+    ///
+    /// Only available with the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn par_filter<F: Filter + Sync>(&self, f: &F) -> Paths {
+        self.par_flat_map(|path| path_filter(path, f))
+    }
+
+    /// Filters paths, locating the points where the filter result changes
+    /// by bisection.
+    ///
+    /// Only the path vertices are tested up front. Each segment whose
+    /// endpoints disagree (one kept, one rejected) is bisected until the
+    /// transition is located to within `tolerance`, and the path is cut
+    /// there. Segments whose endpoints agree are kept or dropped whole, so
+    /// features smaller than the vertex spacing are not detected; chop the
+    /// paths first to bound that spacing. Each transition is halved at most
+    /// 64 times, so a `tolerance` of zero locates it as closely as floating
+    /// point allows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Filter, Paths, Vector};
+    ///
+    /// struct LeftHalf;
+    ///
+    /// impl Filter for LeftHalf {
+    ///     fn filter(&self, v: Vector) -> Option<Vector> {
+    ///         if v.x < 0.3 { Some(v) } else { None }
+    ///     }
+    /// }
+    ///
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(0.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)]]);
+    /// let clipped = paths.filter_bisect(&LeftHalf, 1e-6);
+    /// let end = clipped.paths[0][1];
+    /// assert!((end.x - 0.3).abs() < 1e-5);
+    ///
+    /// let exact = paths.filter_bisect(&LeftHalf, 0.0);
+    /// assert!((exact.paths[0][1].x - 0.3).abs() < 1e-15);
+    /// ```
+    pub fn filter_bisect<F: Filter>(&self, f: &F, tolerance: f64) -> Paths {
+        self.flat_map(|path| path_filter_bisect(path, f, tolerance))
+    }
+
+    /// Parallel version of [`Paths::filter_bisect`].
+    ///
+    /// Only available with the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn par_filter_bisect<F: Filter + Sync>(&self, f: &F, tolerance: f64) -> Paths {
+        self.par_flat_map(|path| path_filter_bisect(path, f, tolerance))
+    }
+
+    /// Splits paths into two sets with a single classification per point.
+    ///
+    /// `f` maps a point to `Some((true, w))` to keep `w` in the first set,
+    /// `Some((false, w))` to keep it in the second, or `None` to drop it.
+    /// With a `tolerance`, the points where the class changes are located by
+    /// bisection as in [`Paths::filter_bisect`]. Both sets keep the styles.
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) fn partition<F: Fn(Vector) -> Option<(bool, Vector)>>(
+        &self,
+        f: &F,
+        tolerance: Option<f64>,
+    ) -> (Paths, Paths) {
+        let parts = self
             .paths
             .iter()
-            .map(|path| path_chop(path, step))
+            .map(|path| path_partition(path, f, tolerance));
+        self.collect_parts(parts)
+    }
+
+    /// Parallel version of `Paths::partition`.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn par_partition<F: Fn(Vector) -> Option<(bool, Vector)> + Sync>(
+        &self,
+        f: &F,
+        tolerance: Option<f64>,
+    ) -> (Paths, Paths) {
+        use rayon::prelude::*;
+        let parts: Vec<_> = self
+            .paths
+            .par_iter()
+            .map(|path| path_partition(path, f, tolerance))
             .collect();
-        Paths { paths }
+        self.collect_parts(parts.into_iter())
     }
 
-    /// Filters paths using a custom filter.
-    pub fn filter<F: Filter>(&self, f: &F) -> Paths {
-        let mut result = Vec::new();
-        for path in &self.paths {
-            result.extend(path_filter(path, f));
+    /// Gathers the two sets of pieces of each path, which inherit its style.
+    fn collect_parts<I: Iterator<Item = [Vec<Path>; 2]>>(&self, parts: I) -> (Paths, Paths) {
+        let mut first = Paths::new();
+        let mut second = Paths::new();
+        for (i, [a, b]) in parts.enumerate() {
+            for p in a {
+                first.push_styled(p, self.style_arc(i));
+            }
+            for p in b {
+                second.push_styled(p, self.style_arc(i));
+            }
+        }
+        (first, second)
+    }
+
+    /// Maps each path to a new path, keeping its style.
+    fn map<G: Fn(&Path) -> Path>(&self, g: G) -> Paths {
+        Paths {
+            paths: self.paths.iter().map(g).collect(),
+            styles: self.styles.clone(),
+        }
+    }
+
+    /// Maps each path to any number of new paths, which inherit its style.
+    pub(crate) fn flat_map<G: Fn(&Path) -> Vec<Path>>(&self, g: G) -> Paths {
+        let mut result = Paths::new();
+        for (i, path) in self.paths.iter().enumerate() {
+            for p in g(path) {
+                result.push_styled(p, self.style_arc(i));
+            }
+        }
+        result
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_flat_map<G: Fn(&Path) -> Vec<Path> + Send + Sync>(&self, g: G) -> Paths {
+        use rayon::prelude::*;
+        let mapped: Vec<Vec<Path>> = self.paths.par_iter().map(g).collect();
+        let mut result = Paths::new();
+        for (i, paths) in mapped.into_iter().enumerate() {
+            for p in paths {
+                result.push_styled(p, self.style_arc(i));
+            }
         }
-        Paths { paths: result }
+        result
     }
 
     /// Simplifies paths by removing redundant points.
@@ -130,37 +360,315 @@ impl Paths {
     /// Uses the Ramer-Douglas-Peucker algorithm to reduce the number of
     /// points while preserving the overall shape.
     pub fn simplify(&self, threshold: f64) -> Paths {
-        let paths = self
-            .paths
-            .iter()
-            .map(|path| path_simplify(path, threshold))
-            .collect();
-        Paths { paths }
+        self.map(|path| path_simplify(path, threshold))
+    }
+
+    /// Joins paths whose endpoints lie within `tolerance` of each other into
+    /// longer paths, reversing paths where needed.
+    ///
+    /// Only paths with the same style are joined. Distances are measured in
+    /// the xy-plane. A joined path takes the place of its first path, and
+    /// empty paths are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![
+    ///     vec![Vector::new(0.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)],
+    ///     vec![Vector::new(2.0, 1.0, 0.0), Vector::new(1.0, 0.0, 0.0)],
+    /// ]);
+    ///
+    /// let merged = paths.merge(1e-6);
+    /// assert_eq!(merged.paths.len(), 1);
+    /// assert_eq!(merged.paths[0][2], Vector::new(2.0, 1.0, 0.0));
+    /// ```
+    pub fn merge(&self, tolerance: f64) -> Paths {
+        plot::merge_paths(self, tolerance)
+    }
+
+    /// Removes segments, or parts of segments, that overlap segments
+    /// emitted before them to within `tolerance`, whichever way they run.
+    ///
+    /// Only paths with the same style are compared. Distances are measured
+    /// in the xy-plane. Paths are split where parts are removed, and paths
+    /// left with no segments are dropped. Single-point paths, which draw
+    /// dots, are kept unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![
+    ///     vec![Vector::new(0.0, 0.0, 0.0), Vector::new(2.0, 0.0, 0.0)],
+    ///     vec![Vector::new(3.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)],
+    /// ]);
+    ///
+    /// // Only the part of the second path beyond the first is kept.
+    /// let deduped = paths.dedupe(1e-6);
+    /// assert_eq!(deduped.paths[1], vec![Vector::new(3.0, 0.0, 0.0), Vector::new(2.0, 0.0, 0.0)]);
+    ///
+    /// let dot = Paths::from_vec(vec![vec![Vector::new(1.0, 1.0, 0.0)]]);
+    /// assert_eq!(dot.dedupe(1e-6).paths, dot.paths);
+    /// ```
+    pub fn dedupe(&self, tolerance: f64) -> Paths {
+        plot::dedupe_paths(self, tolerance)
+    }
+
+    /// Returns the total distance the pen travels between paths when they
+    /// are drawn in order, starting from the origin.
+    ///
+    /// Distances are measured in the xy-plane. Empty paths are skipped.
+    pub fn pen_up_distance(&self) -> f64 {
+        plot::pen_up_distance(self)
+    }
+
+    /// Reorders paths, reversing them where that helps, to reduce the pen-up
+    /// travel of a plotter drawing them.
+    ///
+    /// Paths are ordered by a greedy nearest-neighbour pass over a spatial
+    /// index of their endpoints, then refined by 2-opt moves. Paths sharing a
+    /// style stay together, with groups in the order [`Paths::to_svg`] writes
+    /// them, so that a plotter changes pens once per style. Empty paths are
+    /// dropped. If the new order is no shorter, the other paths are returned
+    /// in their original order.
+    ///
+    /// Returns the reordered paths and the pen-up distance before and after.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![
+    ///     vec![Vector::new(10.0, 0.0, 0.0), Vector::new(20.0, 0.0, 0.0)],
+    ///     vec![Vector::new(10.0, 0.0, 0.0), Vector::new(0.0, 0.0, 0.0)],
+    /// ]);
+    ///
+    /// let (ordered, report) = paths.optimize_order();
+    /// assert_eq!(report.pen_up_before, 20.0);
+    /// assert_eq!(report.pen_up_after, 0.0);
+    /// assert_eq!(ordered.paths[0][0], Vector::new(0.0, 0.0, 0.0));
+    ///
+    /// // Already in order, but the empty path is still dropped.
+    /// let mut with_empty = ordered.clone();
+    /// with_empty.push(Vec::new());
+    /// assert_eq!(with_empty.optimize_order().0.paths, ordered.paths);
+    /// ```
+    pub fn optimize_order(&self) -> (Paths, OrderReport) {
+        let before = self.pen_up_distance();
+        let mut result = Paths::new();
+        let mut pen = Vector::default();
+        for (_, indices) in self.style_groups() {
+            for (i, reversed) in plot::order_paths(&self.paths, &indices, pen) {
+                let mut path = self.paths[i].clone();
+                if reversed {
+                    path.reverse();
+                }
+                pen = path[path.len() - 1];
+                result.push_styled(path, self.style_arc(i));
+            }
+        }
+        let after = result.pen_up_distance();
+        if after >= before {
+            let report = OrderReport {
+                pen_up_before: before,
+                pen_up_after: before,
+            };
+            let mut unchanged = Paths::new();
+            for (i, path) in self.paths.iter().enumerate() {
+                if !path.is_empty() {
+                    unchanged.push_styled(path.clone(), self.style_arc(i));
+                }
+            }
+            return (unchanged, report);
+        }
+        (
+            result,
+            OrderReport {
+                pen_up_before: before,
+                pen_up_after: after,
+            },
+        )
     }
 
     /// Converts the paths to an SVG string.
     ///
+    /// Unstyled paths are drawn as black polylines. Styled paths are grouped
+    /// by style into `<g>` elements carrying the stroke attributes. Styles
+    /// with a layer name are placed in a top-level Inkscape layer of that
+    /// name (one per distinct layer), which plotter software such as the
+    /// AxiDraw extension can plot separately.
+    ///
     /// # Arguments
     ///
     /// * `width` - The SVG width
     /// * `height` - The SVG height
     pub fn to_svg(&self, width: f64, height: f64) -> String {
+        let groups = self.style_groups();
+        let has_layers = groups
+            .iter()
+            .any(|(style, _)| style_layer(*style).is_some());
+        let transform = format!("transform=\"translate(0,{}) scale(1,-1)\"", height);
+
         let mut lines = Vec::new();
         lines.push(format!(
-            "<svg width=\"{}\" height=\"{}\" version=\"1.1\" baseProfile=\"full\" xmlns=\"http://www.w3.org/2000/svg\">",
-            width, height
-        ));
-        lines.push(format!(
-            "<g transform=\"translate(0,{}) scale(1,-1)\">",
-            height
+            "<svg width=\"{}\" height=\"{}\" version=\"1.1\" baseProfile=\"full\" xmlns=\"http://www.w3.org/2000/svg\"{}>",
+            width,
+            height,
+            if has_layers {
+                " xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\""
+            } else {
+                ""
+            }
         ));
-        for path in &self.paths {
-            lines.push(path_to_svg(path));
+        lines.push(format!("<g {}>", transform));
+        let mut current_layer = None;
+        for (style, indices) in &groups {
+            let layer = style_layer(*style);
+            if layer != current_layer {
+                lines.push("</g>".to_string());
+                lines.push(format!(
+                    "<g inkscape:groupmode=\"layer\" inkscape:label=\"{}\" {}>",
+                    escape_xml(layer.unwrap_or_default()),
+                    transform
+                ));
+                current_layer = layer;
+            }
+            match style {
+                Some(style) => {
+                    lines.push(format!("<g {}>", style_to_svg(style)));
+                    for &i in indices {
+                        lines.push(path_to_svg_points(&self.paths[i]));
+                    }
+                    lines.push("</g>".to_string());
+                }
+                None => {
+                    for &i in indices {
+                        lines.push(path_to_svg(&self.paths[i]));
+                    }
+                }
+            }
         }
         lines.push("</g></svg>".to_string());
         lines.join("\n")
     }
 
+    /// Converts the paths to HPGL for a pen plotter.
+    ///
+    /// Render coordinates are multiplied by `scale` to give millimetres and
+    /// written in plotter units of 1/40 mm. Paths are grouped by style in
+    /// the same order as [`Paths::to_svg`]. If any style has a layer, each
+    /// distinct layer (with unlayered paths first) is drawn with its own
+    /// pen, numbered from 1; otherwise everything is drawn with pen 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The render height, used to keep the drawing in the
+    ///   positive quadrant when rotating
+    /// * `scale` - Millimetres per render unit
+    /// * `landscape` - Rotate the drawing a quarter turn anticlockwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(0.0, 0.0, 0.0), Vector::new(10.0, 5.0, 0.0)]]);
+    ///
+    /// let hpgl = paths.to_hpgl(100.0, 0.5, false);
+    /// assert_eq!(hpgl, "IN;\nSP1;\nPU0,0;\nPD200,100;\nPU;\nSP0;\n");
+    /// ```
+    pub fn to_hpgl(&self, height: f64, scale: f64, landscape: bool) -> String {
+        let units = scale * HPGL_UNITS_PER_MM;
+        let position = |v: &Vector| {
+            let (x, y) = if landscape {
+                (height - v.y, v.x)
+            } else {
+                (v.x, v.y)
+            };
+            format!(
+                "{},{}",
+                (x * units).round() as i64,
+                (y * units).round() as i64
+            )
+        };
+
+        let mut lines = vec!["IN;".to_string()];
+        let mut layers: Vec<Option<&str>> = Vec::new();
+        let mut pen_at: Option<String> = None;
+        for (style, indices) in self.style_groups() {
+            let layer = style_layer(style);
+            if !layers.contains(&layer) {
+                layers.push(layer);
+                if pen_at.take().is_some() {
+                    lines.push("PU;".to_string());
+                }
+                lines.push(format!("SP{};", layers.len()));
+            }
+            for &i in &indices {
+                let path = &self.paths[i];
+                if path.is_empty() {
+                    continue;
+                }
+                let start = position(&path[0]);
+                if pen_at.as_ref() != Some(&start) {
+                    lines.push(format!("PU{};", start));
+                    if path.len() == 1 {
+                        lines.push("PD;".to_string());
+                    }
+                }
+                if path.len() > 1 {
+                    let coords: Vec<String> = path.iter().skip(1).map(position).collect();
+                    lines.push(format!("PD{};", coords.join(",")));
+                }
+                pen_at = Some(position(&path[path.len() - 1]));
+            }
+        }
+        lines.push("PU;".to_string());
+        lines.push("SP0;".to_string());
+        let mut hpgl = lines.join("\n");
+        hpgl.push('\n');
+        hpgl
+    }
+
+    /// Writes the paths to an HPGL file.
+    ///
+    /// See [`Paths::to_hpgl`].
+    pub fn write_to_hpgl(
+        &self,
+        path: &str,
+        height: f64,
+        scale: f64,
+        landscape: bool,
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.to_hpgl(height, scale, landscape))
+    }
+
+    /// Groups path indices by style, in order of first appearance. Groups
+    /// without a layer come first, followed by the groups of each layer.
+    pub(crate) fn style_groups(&self) -> Vec<(Option<&Style>, Vec<usize>)> {
+        let mut groups: Vec<(Option<&Style>, Vec<usize>)> = Vec::new();
+        for i in 0..self.paths.len() {
+            let style = self.style(i);
+            match groups.iter_mut().find(|(s, _)| *s == style) {
+                Some((_, indices)) => indices.push(i),
+                None => groups.push((style, vec![i])),
+            }
+        }
+        let mut layers: Vec<Option<&str>> = vec![None];
+        for (style, _) in &groups {
+            let layer = style_layer(*style);
+            if !layers.contains(&layer) {
+                layers.push(layer);
+            }
+        }
+        groups.sort_by_key(|(style, _)| layers.iter().position(|l| *l == style_layer(*style)));
+        groups
+    }
+
     /// Writes the paths to an SVG file.
     ///
     /// # Example
@@ -185,6 +693,103 @@ impl Paths {
         std::fs::write(path, svg)
     }
 
+    /// Converts the paths to G-code for a pen plotter or laser engraver.
+    ///
+    /// Paths are drawn in order, so [`Paths::optimize_order`] is usually
+    /// applied first. See [`GcodeOptions`] for the pen commands, feed rates
+    /// and scaling.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{GcodeOptions, Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(1.0, 2.0, 0.0), Vector::new(3.0, 2.0, 0.0)]]);
+    /// let options = GcodeOptions::new().with_scale(0.5).with_header("").with_footer("");
+    ///
+    /// assert_eq!(
+    ///     paths.to_gcode(&options),
+    ///     "G0 X0.500 Y1.000\nG1 Z0.000 F3000.000\nG1 X1.500 Y1.000 F3000.000\nG0 Z5.000\n"
+    /// );
+    /// ```
+    pub fn to_gcode(&self, options: &GcodeOptions) -> String {
+        gcode::paths_to_gcode(self, options)
+    }
+
+    /// Writes the paths to a G-code file.
+    ///
+    /// See [`Paths::to_gcode`].
+    pub fn write_to_gcode(&self, path: &str, options: &GcodeOptions) -> std::io::Result<()> {
+        std::fs::write(path, self.to_gcode(options))
+    }
+
+    /// Converts the paths to a single-page vector PDF.
+    ///
+    /// The `width` x `height` render area is scaled to fit the page
+    /// described by `options` and centred on it. Paths are drawn as black
+    /// lines of the configured width.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{PageOptions, Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(0.0, 0.0, 0.0), Vector::new(1.0, 1.0, 0.0)]]);
+    /// let pdf = paths.to_pdf(1.0, 1.0, &PageOptions::new());
+    /// assert!(pdf.starts_with(b"%PDF-1.4"));
+    /// assert!(pdf.ends_with(b"%%EOF\n"));
+    /// ```
+    pub fn to_pdf(&self, width: f64, height: f64, options: &PageOptions) -> Vec<u8> {
+        print::paths_to_pdf(self, width, height, options)
+    }
+
+    /// Writes the paths to a PDF file.
+    ///
+    /// See [`Paths::to_pdf`].
+    pub fn write_to_pdf(
+        &self,
+        path: &str,
+        width: f64,
+        height: f64,
+        options: &PageOptions,
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.to_pdf(width, height, options))
+    }
+
+    /// Converts the paths to Encapsulated PostScript.
+    ///
+    /// The layout is the same as [`Paths::to_pdf`], and the bounding box is
+    /// the whole page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{PageOptions, Paths, Vector};
+    ///
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(0.0, 0.0, 0.0), Vector::new(1.0, 1.0, 0.0)]]);
+    /// let options = PageOptions::new().with_page_size(25.4, 25.4).with_margin(0.0);
+    ///
+    /// let eps = paths.to_eps(1.0, 1.0, &options);
+    /// assert!(eps.contains("%%BoundingBox: 0 0 72 72"));
+    /// assert!(eps.contains("0 0 newpath moveto\n72 72 lineto\nstroke"));
+    /// ```
+    pub fn to_eps(&self, width: f64, height: f64, options: &PageOptions) -> String {
+        print::paths_to_eps(self, width, height, options)
+    }
+
+    /// Writes the paths to an EPS file.
+    ///
+    /// See [`Paths::to_eps`].
+    pub fn write_to_eps(
+        &self,
+        path: &str,
+        width: f64,
+        height: f64,
+        options: &PageOptions,
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.to_eps(width, height, options))
+    }
+
     /// Writes the paths to a PNG image file.
     ///
     /// Renders the paths as black lines on a white background.
@@ -321,6 +926,92 @@ fn path_chop(path: &Path, step: f64) -> Path {
     result
 }
 
+fn path_chop_projected(path: &Path, matrix: &Matrix, width: f64, height: f64, step: f64) -> Path {
+    let segment = ProjectedSegment {
+        matrix,
+        width,
+        height,
+        step,
+    };
+    let mut result = Vec::new();
+    if let Some(first) = path.first() {
+        result.push(*first);
+    }
+    for w in path.windows(2) {
+        segment.chop_near(w[0], w[1], &mut result);
+    }
+    result
+}
+
+/// Maximum number of times a single segment is halved by `chop_projected`.
+const MAX_PROJECTED_CHOP_DEPTH: usize = 20;
+
+struct ProjectedSegment<'a> {
+    matrix: &'a Matrix,
+    width: f64,
+    height: f64,
+    step: f64,
+}
+
+impl ProjectedSegment<'_> {
+    /// Pushes the interior points of `a`-`b` and then `b` onto `result`,
+    /// leaving the part of the segment behind the near plane unchopped.
+    fn chop_near(&self, a: Vector, b: Vector, result: &mut Path) {
+        let da = self.near_distance(a);
+        let db = self.near_distance(b);
+        if da >= 0.0 && db >= 0.0 {
+            self.chop(a, b, 0, result);
+        } else if da < 0.0 && db < 0.0 {
+            result.push(b);
+        } else {
+            let n = a.add(b.sub(a).mul_scalar(da / (da - db)));
+            if da < 0.0 {
+                result.push(n);
+                self.chop(n, b, 0, result);
+            } else {
+                self.chop(a, n, 0, result);
+                result.push(b);
+            }
+        }
+    }
+
+    /// Returns a value that is positive in front of the near plane, zero on
+    /// it and negative behind it, and varies linearly along a segment.
+    fn near_distance(&self, v: Vector) -> f64 {
+        let (p, w) = self.matrix.mul_position_homogeneous(v);
+        p.z + w
+    }
+
+    /// Pushes the interior points of `a`-`b` and then `b` onto `result`.
+    fn chop(&self, a: Vector, b: Vector, depth: usize, result: &mut Path) {
+        if depth < MAX_PROJECTED_CHOP_DEPTH && self.needs_split(a, b) {
+            let m = a.add(b).mul_scalar(0.5);
+            self.chop(a, m, depth + 1, result);
+            self.chop(m, b, depth + 1, result);
+        } else {
+            result.push(b);
+        }
+    }
+
+    fn needs_split(&self, a: Vector, b: Vector) -> bool {
+        let (pa, wa) = self.matrix.mul_position_homogeneous(a);
+        let (pb, wb) = self.matrix.mul_position_homogeneous(b);
+        if wa <= 0.0 || wb <= 0.0 {
+            // Behind the eye, which `chop_near` only passes on by rounding.
+            return false;
+        }
+        let outside = |p: Vector, w: f64| [p.x > w, p.x < -w, p.y > w, p.y < -w, p.z > w, p.z < -w];
+        let (oa, ob) = (outside(pa, wa), outside(pb, wb));
+        if oa.iter().zip(ob.iter()).any(|(a, b)| *a && *b) {
+            // Both endpoints are beyond the same clip plane.
+            return false;
+        }
+        let dx = (pb.x / wb - pa.x / wa) * self.width / 2.0;
+        let dy = (pb.y / wb - pa.y / wa) * self.height / 2.0;
+        dx.hypot(dy) > self.step
+    }
+}
+
 fn path_filter<F: Filter>(path: &Path, f: &F) -> Vec<Path> {
     let mut result = Vec::new();
     let mut current_path = Vec::new();
@@ -343,6 +1034,167 @@ fn path_filter<F: Filter>(path: &Path, f: &F) -> Vec<Path> {
     result
 }
 
+fn path_filter_bisect<F: Filter>(path: &Path, f: &F, tolerance: f64) -> Vec<Path> {
+    let mut result = Vec::new();
+    let mut current_path = Vec::new();
+    let mut previous: Option<(Vector, Option<Vector>)> = None;
+
+    for v in path {
+        let w = f.filter(*v);
+        if let Some((u, uw)) = previous {
+            if uw.is_some() != w.is_some() {
+                if let Some(edge) = bisect_transition(f, u, uw, *v, w, tolerance) {
+                    current_path.push(edge);
+                }
+                if uw.is_some() {
+                    if current_path.len() > 1 {
+                        result.push(current_path);
+                    }
+                    current_path = Vec::new();
+                }
+            }
+        }
+        if let Some(w) = w {
+            current_path.push(w);
+        }
+        previous = Some((*v, w));
+    }
+
+    if current_path.len() > 1 {
+        result.push(current_path);
+    }
+
+    result
+}
+
+/// The class of a point for `Paths::partition`: the side it is kept on
+/// and the point kept, or `None` if it is dropped.
+type Class = Option<(bool, Vector)>;
+
+/// Splits a path into the pieces of the first and of the second class of
+/// `f` (see `Paths::partition`).
+fn path_partition<F: Fn(Vector) -> Class>(
+    path: &Path,
+    f: &F,
+    tolerance: Option<f64>,
+) -> [Vec<Path>; 2] {
+    let mut result: [Vec<Path>; 2] = [Vec::new(), Vec::new()];
+    let mut current: [Path; 2] = [Vec::new(), Vec::new()];
+    let index = |side: bool| if side { 0 } else { 1 };
+    let mut previous: Option<(Vector, Class)> = None;
+
+    for v in path {
+        let c = f(*v);
+        if let Some((u, uc)) = previous {
+            let (from, to) = (uc.map(|x| x.0), c.map(|x| x.0));
+            if from != to {
+                let (last, first) = match tolerance {
+                    Some(tolerance) => bisect_partition(f, u, uc, *v, c, tolerance),
+                    None => (None, None),
+                };
+                if let Some((side, w)) = last {
+                    current[index(side)].push(w);
+                }
+                if let Some(side) = from {
+                    let piece = std::mem::take(&mut current[index(side)]);
+                    if piece.len() > 1 {
+                        result[index(side)].push(piece);
+                    }
+                }
+                if let Some((side, w)) = first {
+                    current[index(side)].push(w);
+                }
+            }
+        }
+        if let Some((side, w)) = c {
+            current[index(side)].push(w);
+        }
+        previous = Some((*v, c));
+    }
+
+    for (i, piece) in current.into_iter().enumerate() {
+        if piece.len() > 1 {
+            result[i].push(piece);
+        }
+    }
+    result
+}
+
+/// Bisects the segment `a`-`b`, whose endpoints have different classes,
+/// and returns the classified points on either side of the transition.
+/// Points of a class other than that of `a` or `b` are dropped.
+fn bisect_partition<F: Fn(Vector) -> Class>(
+    f: &F,
+    a: Vector,
+    ac: Class,
+    b: Vector,
+    bc: Class,
+    tolerance: f64,
+) -> (Class, Class) {
+    let class = ac.map(|x| x.0);
+    let (mut lo, mut lo_c) = (a, ac);
+    let (mut hi, mut hi_c) = (b, bc);
+    for _ in 0..MAX_BISECT_STEPS {
+        if lo.distance(hi) <= tolerance {
+            break;
+        }
+        let mid = lo.add(hi).mul_scalar(0.5);
+        let mid_c = f(mid);
+        if mid_c.map(|x| x.0) == class {
+            lo = mid;
+            lo_c = mid_c;
+        } else {
+            hi = mid;
+            hi_c = mid_c;
+        }
+    }
+    let first = if hi_c.map(|x| x.0) == bc.map(|x| x.0) {
+        hi_c
+    } else {
+        None
+    };
+    (lo_c, first)
+}
+
+/// Maximum number of halvings used to locate a visibility transition, which
+/// is enough to reach adjacent floating point values.
+const MAX_BISECT_STEPS: usize = 64;
+
+/// Bisects the segment `a`-`b`, whose endpoints have different filter
+/// results, and returns the filtered point on the kept side of the
+/// transition.
+fn bisect_transition<F: Filter>(
+    f: &F,
+    a: Vector,
+    aw: Option<Vector>,
+    b: Vector,
+    bw: Option<Vector>,
+    tolerance: f64,
+) -> Option<Vector> {
+    let keep_a = aw.is_some();
+    let (mut lo, mut lo_w) = (a, aw);
+    let (mut hi, mut hi_w) = (b, bw);
+    for _ in 0..MAX_BISECT_STEPS {
+        if lo.distance(hi) <= tolerance {
+            break;
+        }
+        let mid = lo.add(hi).mul_scalar(0.5);
+        let mid_w = f.filter(mid);
+        if mid_w.is_some() == keep_a {
+            lo = mid;
+            lo_w = mid_w;
+        } else {
+            hi = mid;
+            hi_w = mid_w;
+        }
+    }
+    if keep_a {
+        lo_w
+    } else {
+        hi_w
+    }
+}
+
 fn path_simplify(path: &Path, threshold: f64) -> Path {
     if path.len() < 3 {
         return path.clone();
@@ -379,3 +1231,35 @@ fn path_to_svg(path: &Path) -> String {
         points
     )
 }
+
+fn path_to_svg_points(path: &Path) -> String {
+    let coords: Vec<String> = path.iter().map(|v| format!("{},{}", v.x, v.y)).collect();
+    format!("<polyline points=\"{}\" />", coords.join(" "))
+}
+
+fn style_to_svg(style: &Style) -> String {
+    let mut attrs = vec![
+        format!("stroke=\"{}\"", escape_xml(&style.color)),
+        format!("stroke-width=\"{}\"", style.width),
+        "fill=\"none\"".to_string(),
+    ];
+    if style.opacity != 1.0 {
+        attrs.push(format!("stroke-opacity=\"{}\"", style.opacity));
+    }
+    if !style.dash.is_empty() {
+        let dash: Vec<String> = style.dash.iter().map(|d| d.to_string()).collect();
+        attrs.push(format!("stroke-dasharray=\"{}\"", dash.join(",")));
+    }
+    attrs.join(" ")
+}
+
+fn style_layer(style: Option<&Style>) -> Option<&str> {
+    style.and_then(|s| s.layer.as_deref())
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,18 +1,56 @@
 use crate::common::EPS;
 use crate::mesh::Mesh;
-use crate::path::Paths;
+use crate::path::{Path, Paths};
+use crate::ray::Ray;
+use crate::shape::Shape;
 use crate::triangle::Triangle;
 use crate::vector::Vector;
 
+/// Default number of grid cells across the larger side of the area sampled
+/// by [`Plane::section`].
+pub const DEFAULT_SECTION_RESOLUTION: usize = 200;
+
+/// Precision of each section crossing, as a fraction of a grid cell.
+const SECTION_TOLERANCE: f64 = 1e-4;
+
 #[derive(Debug, Clone)]
 pub struct Plane {
     pub point: Vector,
     pub normal: Vector,
+    /// Number of grid cells across the larger side of the area sampled by
+    /// [`Plane::section`]. See [`Plane::with_section_resolution`].
+    pub section_resolution: usize,
 }
 
 impl Plane {
     pub fn new(point: Vector, normal: Vector) -> Self {
-        Plane { point, normal }
+        Plane {
+            point,
+            normal,
+            section_resolution: DEFAULT_SECTION_RESOLUTION,
+        }
+    }
+
+    /// Sets the number of grid cells across a shape that
+    /// [`Plane::section`] samples, which defaults to
+    /// [`DEFAULT_SECTION_RESOLUTION`]. The section outline is precise to a
+    /// small fraction of a cell, but features smaller than a cell may be
+    /// missed. Values below 1 are treated as 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Plane, Sphere, Vector};
+    ///
+    /// let plane = Plane::new(Vector::default(), Vector::new(0.0, 0.0, 1.0))
+    ///     .with_section_resolution(20);
+    /// let section = plane.section(&Sphere::new(Vector::default(), 1.0));
+    /// assert!(section.paths.len() < 100);
+    /// assert!(section.paths.iter().flatten().all(|v| (v.length() - 1.0).abs() < 1e-3));
+    /// ```
+    pub fn with_section_resolution(mut self, resolution: usize) -> Self {
+        self.section_resolution = resolution;
+        self
     }
 
     pub fn intersect_segment(&self, v0: Vector, v1: Vector) -> Option<Vector> {
@@ -26,7 +64,7 @@ impl Plane {
         }
 
         let t = n / d;
-        if t < 0.0 || t > 1.0 {
+        if !(0.0..=1.0).contains(&t) {
             return None;
         }
 
@@ -55,4 +93,170 @@ impl Plane {
         }
         Paths::from_vec(result)
     }
+
+    /// Returns the signed distance from `v` to the plane, positive on the
+    /// side the normal points to.
+    pub fn signed_distance(&self, v: Vector) -> f64 {
+        v.sub(self.point).dot(self.normal.normalize())
+    }
+
+    /// Returns the distance along `r` at which it crosses the plane.
+    pub fn intersect_ray(&self, r: Ray) -> Option<f64> {
+        let d = self.normal.dot(r.direction);
+        if d > -EPS && d < EPS {
+            return None;
+        }
+        let t = self.normal.dot(self.point.sub(r.origin)) / d;
+        if t < 0.0 {
+            return None;
+        }
+        Some(t)
+    }
+
+    /// Cuts paths at the plane, keeping the parts on the side opposite the
+    /// normal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Paths, Plane, Vector};
+    ///
+    /// let plane = Plane::new(Vector::default(), Vector::new(1.0, 0.0, 0.0));
+    /// let paths = Paths::from_vec(vec![vec![Vector::new(-1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)]]);
+    ///
+    /// let clipped = plane.clip_paths(&paths);
+    /// assert_eq!(clipped.paths[0][1], Vector::new(0.0, 0.0, 0.0));
+    /// ```
+    pub fn clip_paths(&self, paths: &Paths) -> Paths {
+        paths.flat_map(|path| self.clip_path(path))
+    }
+
+    fn clip_path(&self, path: &Path) -> Vec<Path> {
+        let mut result = Vec::new();
+        let mut current_path = Vec::new();
+        for (i, v) in path.iter().enumerate() {
+            let d = self.signed_distance(*v);
+            if i > 0 {
+                let u = path[i - 1];
+                let du = self.signed_distance(u);
+                if (du <= 0.0) != (d <= 0.0) {
+                    let crossing = u.add(v.sub(u).mul_scalar(du / (du - d)));
+                    current_path.push(crossing);
+                    if du <= 0.0 {
+                        if current_path.len() > 1 {
+                            result.push(current_path);
+                        }
+                        current_path = Vec::new();
+                    }
+                }
+            }
+            if d <= 0.0 {
+                current_path.push(*v);
+            }
+        }
+        if current_path.len() > 1 {
+            result.push(current_path);
+        }
+        result
+    }
+
+    /// Computes the outline of the cross-section of a solid with this plane.
+    ///
+    /// The part of the plane inside the shape's bounding box is sampled on
+    /// a grid of [`section_resolution`](Plane::section_resolution) cells
+    /// with [`Shape::contains`] and the outline is traced with marching
+    /// squares, with each crossing refined by bisection. Shapes whose
+    /// `contains` always returns `false` have no section.
+    pub fn section<S: Shape + ?Sized>(&self, shape: &S) -> Paths {
+        let n = self.normal.normalize();
+        let u = n.cross(n.min_axis()).normalize();
+        let v = n.cross(u);
+
+        let bx = shape.bounding_box();
+        let (mut u0, mut u1, mut v0, mut v1) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        let (mut d0, mut d1) = (f64::MAX, f64::MIN);
+        for i in 0..8 {
+            let corner = Vector::new(
+                if i & 1 == 0 { bx.min.x } else { bx.max.x },
+                if i & 2 == 0 { bx.min.y } else { bx.max.y },
+                if i & 4 == 0 { bx.min.z } else { bx.max.z },
+            );
+            let c = corner.sub(self.point);
+            u0 = u0.min(c.dot(u));
+            u1 = u1.max(c.dot(u));
+            v0 = v0.min(c.dot(v));
+            v1 = v1.max(c.dot(v));
+            d0 = d0.min(c.dot(n));
+            d1 = d1.max(c.dot(n));
+        }
+        if d0 > 0.0 || d1 < 0.0 {
+            return Paths::new();
+        }
+
+        let size = (u1 - u0).max(v1 - v0) / self.section_resolution.max(1) as f64;
+        if size <= 0.0 {
+            return Paths::new();
+        }
+        // One cell of padding on each side so the outline is closed.
+        let nu = ((u1 - u0) / size).ceil() as usize + 2;
+        let nv = ((v1 - v0) / size).ceil() as usize + 2;
+        let at = |i: usize, j: usize| {
+            self.point
+                .add(u.mul_scalar(u0 + (i as f64 - 1.0) * size))
+                .add(v.mul_scalar(v0 + (j as f64 - 1.0) * size))
+        };
+        let inside = |p: Vector| shape.contains(p, 0.0);
+        let grid: Vec<Vec<bool>> = (0..=nu)
+            .map(|i| (0..=nv).map(|j| inside(at(i, j))).collect())
+            .collect();
+
+        // Locates the boundary between two grid points on either side of it.
+        let crossing = |a: Vector, b: Vector| {
+            let a_inside = inside(a);
+            let (mut lo, mut hi) = (a, b);
+            while lo.distance(hi) > size * SECTION_TOLERANCE {
+                let mid = lo.add(hi).mul_scalar(0.5);
+                if inside(mid) == a_inside {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            lo.add(hi).mul_scalar(0.5)
+        };
+
+        let mut result = Vec::new();
+        for i in 0..nu {
+            for j in 0..nv {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let states = corners.map(|(ci, cj)| grid[ci][cj]);
+                if states.iter().all(|s| *s) || states.iter().all(|s| !*s) {
+                    continue;
+                }
+                // Edges whose endpoints differ, in order around the cell.
+                let edges: Vec<Vector> = (0..4)
+                    .filter(|&k| states[k] != states[(k + 1) % 4])
+                    .map(|k| {
+                        let (ai, aj) = corners[k];
+                        let (bi, bj) = corners[(k + 1) % 4];
+                        crossing(at(ai, aj), at(bi, bj))
+                    })
+                    .collect();
+                if edges.len() == 2 {
+                    result.push(vec![edges[0], edges[1]]);
+                } else if edges.len() == 4 {
+                    // Saddle: decide the pairing from the cell center.
+                    let center = at(i, j).add(at(i + 1, j + 1)).mul_scalar(0.5);
+                    if inside(center) == states[0] {
+                        result.push(vec![edges[0], edges[1]]);
+                        result.push(vec![edges[2], edges[3]]);
+                    } else {
+                        result.push(vec![edges[3], edges[0]]);
+                        result.push(vec![edges[1], edges[2]]);
+                    }
+                }
+            }
+        }
+        Paths::from_vec(result)
+    }
 }
@@ -0,0 +1,577 @@
+//! Preparing paths for pen plotters.
+//!
+//! A pen plotter draws paths in the order they are given, lifting the pen to
+//! travel from the end of one path to the start of the next. This module
+//! provides the algorithms behind [`Paths::optimize_order`], which reorders
+//! and reverses paths to shorten that pen-up travel, and behind
+//! [`Paths::merge`] and [`Paths::dedupe`], which join touching paths and
+//! remove segments that would be drawn twice.
+//!
+//! Distances are measured in the xy-plane, the plane of rendered paths, and
+//! the pen is taken to start at the origin.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Cube, Scene, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+//!
+//! let paths = scene.render(
+//!     Vector::new(4.0, 3.0, 2.0),
+//!     Vector::new(0.0, 0.0, 0.0),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     1024.0, 1024.0, 50.0, 0.1, 10.0, 0.01,
+//! );
+//!
+//! let paths = paths.dedupe(0.01).merge(0.01);
+//! let (paths, report) = paths.optimize_order();
+//! println!("pen-up travel: {} -> {}", report.pen_up_before, report.pen_up_after);
+//! paths.write_to_svg("output.svg", 1024.0, 1024.0).unwrap();
+//! ```
+
+use crate::path::{Path, Paths};
+use crate::vector::Vector;
+use std::collections::HashMap;
+
+/// Number of following paths each position is compared against by the 2-opt
+/// refinement.
+const TWO_OPT_WINDOW: usize = 64;
+
+/// Maximum number of 2-opt passes over the whole sequence.
+const TWO_OPT_PASSES: usize = 8;
+
+/// Pen-up travel of a set of paths before and after
+/// [`Paths::optimize_order`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OrderReport {
+    /// Pen-up distance of the paths in their original order.
+    pub pen_up_before: f64,
+    /// Pen-up distance of the paths in their optimised order.
+    pub pen_up_after: f64,
+}
+
+impl OrderReport {
+    /// Returns the pen-up distance saved by the new order.
+    pub fn saved(&self) -> f64 {
+        self.pen_up_before - self.pen_up_after
+    }
+}
+
+/// Returns the total pen-up distance needed to draw `paths` in order,
+/// starting from the origin. Empty paths are skipped.
+pub(crate) fn pen_up_distance(paths: &Paths) -> f64 {
+    let mut pen = Vector::default();
+    let mut total = 0.0;
+    for path in paths.paths.iter().filter(|p| !p.is_empty()) {
+        total += pen.distance(flat(path[0]));
+        pen = flat(path[path.len() - 1]);
+    }
+    total
+}
+
+/// Orders the non-empty paths at `indices` for drawing with the pen starting
+/// at `start`. Returns each path index with `true` if the path should be
+/// drawn in reverse.
+///
+/// A greedy pass repeatedly travels to the nearest unused path endpoint,
+/// found with a grid over the endpoints. The sequence is then refined by
+/// 2-opt moves, each of which reverses a run of paths when that shortens the
+/// travel into and out of the run. Moves are limited to runs of at most
+/// [`TWO_OPT_WINDOW`] paths so that a pass takes linear time.
+pub(crate) fn order_paths(paths: &[Path], indices: &[usize], start: Vector) -> Vec<(usize, bool)> {
+    let ends: Vec<(usize, Vector, Vector)> = indices
+        .iter()
+        .filter(|&&i| !paths[i].is_empty())
+        .map(|&i| {
+            let path = &paths[i];
+            (i, flat(path[0]), flat(path[path.len() - 1]))
+        })
+        .collect();
+    if ends.is_empty() {
+        return Vec::new();
+    }
+
+    let mut grid = EndpointGrid::new(&ends);
+    let mut used = vec![false; ends.len()];
+    let mut order: Vec<(usize, bool)> = Vec::with_capacity(ends.len());
+    let mut pen = flat(start);
+    while order.len() < ends.len() {
+        let (k, reversed) = grid.nearest(pen, &ends, &used);
+        used[k] = true;
+        order.push((k, reversed));
+        pen = if reversed { ends[k].1 } else { ends[k].2 };
+    }
+
+    two_opt(&mut order, &ends, flat(start));
+    order.into_iter().map(|(k, r)| (ends[k].0, r)).collect()
+}
+
+/// Refines `order` by reversing runs of paths while that shortens the
+/// pen-up travel.
+fn two_opt(order: &mut [(usize, bool)], ends: &[(usize, Vector, Vector)], start: Vector) {
+    let first = |o: (usize, bool)| if o.1 { ends[o.0].2 } else { ends[o.0].1 };
+    let last = |o: (usize, bool)| if o.1 { ends[o.0].1 } else { ends[o.0].2 };
+    let n = order.len();
+    for _ in 0..TWO_OPT_PASSES {
+        let mut improved = false;
+        for i in 0..n {
+            let a = if i == 0 { start } else { last(order[i - 1]) };
+            for j in i..n.min(i + TWO_OPT_WINDOW) {
+                // Reversing order[i..=j] replaces the travel a -> b and
+                // c -> d with a -> c and b -> d. The travel inside the run
+                // is unchanged.
+                let b = first(order[i]);
+                let c = last(order[j]);
+                let (old, new) = match order.get(j + 1) {
+                    Some(&next) => {
+                        let d = first(next);
+                        (a.distance(b) + c.distance(d), a.distance(c) + b.distance(d))
+                    }
+                    None => (a.distance(b), a.distance(c)),
+                };
+                if new < old - 1e-9 {
+                    order[i..=j].reverse();
+                    for o in &mut order[i..=j] {
+                        o.1 = !o.1;
+                    }
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+}
+
+/// Projects `v` onto the xy-plane.
+fn flat(v: Vector) -> Vector {
+    Vector::new(v.x, v.y, 0.0)
+}
+
+/// A uniform grid over the start and end points of a set of paths, for
+/// nearest endpoint queries.
+struct EndpointGrid {
+    min: Vector,
+    cell: f64,
+    nx: usize,
+    ny: usize,
+    /// The paths with an endpoint in each cell, with `true` for the end
+    /// point. Entries of used paths are removed lazily.
+    cells: Vec<Vec<(usize, bool)>>,
+}
+
+impl EndpointGrid {
+    fn new(ends: &[(usize, Vector, Vector)]) -> Self {
+        let mut min = ends[0].1;
+        let mut max = ends[0].1;
+        for &(_, a, b) in ends {
+            min = min.min(a).min(b);
+            max = max.max(a).max(b);
+        }
+        let size = max.sub(min);
+        // Aim for about two endpoints per cell.
+        let area = size.x.max(1e-9) * size.y.max(1e-9);
+        let cell = (area / ends.len() as f64).sqrt().max(1e-9);
+        let nx = ((size.x / cell) as usize + 1).min(1 << 12);
+        let ny = ((size.y / cell) as usize + 1).min(1 << 12);
+        let cell = cell.max(size.x / nx as f64).max(size.y / ny as f64);
+        let mut grid = EndpointGrid {
+            min,
+            cell,
+            nx,
+            ny,
+            cells: vec![Vec::new(); nx * ny],
+        };
+        for (k, &(_, a, b)) in ends.iter().enumerate() {
+            let (x, y) = grid.cell_of(a);
+            grid.cells[y * nx + x].push((k, false));
+            let (x, y) = grid.cell_of(b);
+            grid.cells[y * nx + x].push((k, true));
+        }
+        grid
+    }
+
+    /// Returns the cell holding `v`, clamped to the grid.
+    fn cell_of(&self, v: Vector) -> (usize, usize) {
+        let x = ((v.x - self.min.x) / self.cell).max(0.0) as usize;
+        let y = ((v.y - self.min.y) / self.cell).max(0.0) as usize;
+        (x.min(self.nx - 1), y.min(self.ny - 1))
+    }
+
+    /// Returns the unused path with the endpoint nearest to `v`, and `true`
+    /// if that endpoint is the end of the path. At least one path must be
+    /// unused.
+    fn nearest(
+        &mut self,
+        v: Vector,
+        ends: &[(usize, Vector, Vector)],
+        used: &[bool],
+    ) -> (usize, bool) {
+        let (cx, cy) = self.cell_of(v);
+        let (cx, cy) = (cx as isize, cy as isize);
+        let mut best: Option<(f64, usize, bool)> = None;
+        let rings = self.nx.max(self.ny) as isize;
+        for r in 0..=rings {
+            for (x, y) in ring(cx, cy, r) {
+                if x < 0 || y < 0 || x >= self.nx as isize || y >= self.ny as isize {
+                    continue;
+                }
+                let cell = &mut self.cells[y as usize * self.nx + x as usize];
+                cell.retain(|&(k, _)| !used[k]);
+                for &(k, is_end) in cell.iter() {
+                    let p = if is_end { ends[k].2 } else { ends[k].1 };
+                    let d = v.distance_squared(p);
+                    if best.is_none_or(|(bd, _, _)| d < bd) {
+                        best = Some((d, k, is_end));
+                    }
+                }
+            }
+            // Every cell beyond ring r is at least r cells away from `v`.
+            if let Some((d, _, _)) = best {
+                let reach = r as f64 * self.cell;
+                if d <= reach * reach {
+                    break;
+                }
+            }
+        }
+        let (_, k, is_end) = best.expect("no unused paths left");
+        (k, is_end)
+    }
+}
+
+/// Returns the cells on the square ring at Chebyshev distance `r` from
+/// (`cx`, `cy`).
+fn ring(cx: isize, cy: isize, r: isize) -> Vec<(isize, isize)> {
+    if r == 0 {
+        return vec![(cx, cy)];
+    }
+    let mut cells = Vec::with_capacity(8 * r as usize);
+    for x in cx - r..=cx + r {
+        cells.push((x, cy - r));
+        cells.push((x, cy + r));
+    }
+    for y in cy - r + 1..cy + r {
+        cells.push((cx - r, y));
+        cells.push((cx + r, y));
+    }
+    cells
+}
+
+/// Joins paths of the same style whose endpoints lie within `tolerance` of
+/// each other into longer paths.
+///
+/// Each path is extended from its end and then from its start by unused
+/// paths with a matching endpoint, reversing them where needed. Joined paths
+/// take the position of their first path, keeping their direction.
+pub(crate) fn merge_paths(paths: &Paths, tolerance: f64) -> Paths {
+    let groups = group_ids(paths);
+    let mut ends = PointHash::new(tolerance);
+    for (k, path) in paths.paths.iter().enumerate() {
+        if let (Some(&first), Some(&last)) = (path.first(), path.last()) {
+            ends.insert(groups[k], first, (k, false));
+            ends.insert(groups[k], last, (k, true));
+        }
+    }
+
+    let mut used = vec![false; paths.paths.len()];
+    let mut result = Paths::new();
+    for (k, path) in paths.paths.iter().enumerate() {
+        if used[k] || path.is_empty() {
+            continue;
+        }
+        used[k] = true;
+        let mut chain = path.clone();
+        extend_chain(&mut chain, paths, groups[k], &ends, &mut used, tolerance);
+        chain.reverse();
+        extend_chain(&mut chain, paths, groups[k], &ends, &mut used, tolerance);
+        chain.reverse();
+        result.push_styled(chain, paths.style_arc(k));
+    }
+    result
+}
+
+/// Appends unused paths to the end of `chain` while one of them has an
+/// endpoint within `tolerance` of it, stopping if the chain closes.
+fn extend_chain(
+    chain: &mut Path,
+    paths: &Paths,
+    group: usize,
+    ends: &PointHash,
+    used: &mut [bool],
+    tolerance: f64,
+) {
+    while chain.len() < 2 || flat(chain[0]).distance(flat(chain[chain.len() - 1])) > tolerance {
+        let tail = chain[chain.len() - 1];
+        let Some((j, is_end)) = ends.nearest(group, tail, |(j, _)| !used[j]) else {
+            break;
+        };
+        used[j] = true;
+        let mut next = paths.paths[j].clone();
+        if is_end {
+            next.reverse();
+        }
+        chain.extend(next.into_iter().skip(1));
+    }
+}
+
+/// Removes the parts of segments that overlap segments of the same style
+/// emitted before them, in either direction, to within `tolerance`.
+///
+/// Paths are split where segments are removed. Paths left with no segments
+/// are dropped, but single-point paths are passed through unchanged.
+pub(crate) fn dedupe_paths(paths: &Paths, tolerance: f64) -> Paths {
+    let groups = group_ids(paths);
+    let mut total = 0.0;
+    let mut count = 0;
+    for path in &paths.paths {
+        for w in path.windows(2) {
+            total += flat(w[0]).distance(flat(w[1]));
+            count += 1;
+        }
+    }
+    let cell = (total / count.max(1) as f64).max(tolerance).max(1e-9);
+    let mut emitted = SegmentHash::new(cell);
+
+    let mut result = Paths::new();
+    for (k, path) in paths.paths.iter().enumerate() {
+        let style = paths.style_arc(k);
+        if path.len() == 1 {
+            result.push_styled(path.clone(), style);
+            continue;
+        }
+        let mut current: Path = Vec::new();
+        // Whether `current` ends at the end of the previous segment.
+        let mut open = false;
+        for w in path.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let (fa, fb) = (flat(a), flat(b));
+            let length = fa.distance(fb);
+            if length == 0.0 {
+                continue;
+            }
+            let covered = emitted.covered(groups[k], fa, fb, tolerance);
+            let pieces = uncovered(covered, length, tolerance);
+            for &(t0, t1) in &pieces {
+                let p = a.add(b.sub(a).mul_scalar(t0 / length));
+                let q = a.add(b.sub(a).mul_scalar(t1 / length));
+                if !(open && t0 == 0.0) {
+                    if current.len() > 1 {
+                        result.push_styled(current, style.clone());
+                    }
+                    current = vec![p];
+                }
+                current.push(q);
+                open = t1 == length;
+            }
+            if pieces.is_empty() {
+                open = false;
+            }
+            for &(t0, t1) in &pieces {
+                let p = fa.add(fb.sub(fa).mul_scalar(t0 / length));
+                let q = fa.add(fb.sub(fa).mul_scalar(t1 / length));
+                emitted.insert(groups[k], p, q);
+            }
+        }
+        if current.len() > 1 {
+            result.push_styled(current, style);
+        }
+    }
+    result
+}
+
+/// Returns the parts of `0..length` not covered by the `covered` intervals.
+///
+/// Gaps of up to `tolerance` between covered intervals, and at either end,
+/// are treated as covered. If nothing is covered, the whole range is
+/// returned, however short.
+fn uncovered(mut covered: Vec<(f64, f64)>, length: f64, tolerance: f64) -> Vec<(f64, f64)> {
+    if covered.is_empty() {
+        return vec![(0.0, length)];
+    }
+    covered.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut result = Vec::new();
+    let mut t = 0.0;
+    for (t0, t1) in covered {
+        if t0 > t + tolerance {
+            result.push((t, t0));
+        }
+        t = t.max(t1);
+    }
+    if length > t + tolerance {
+        result.push((t, length));
+    }
+    result
+}
+
+/// Returns the index of each path's group in [`Paths::style_groups`].
+fn group_ids(paths: &Paths) -> Vec<usize> {
+    let mut ids = vec![0; paths.paths.len()];
+    for (g, (_, indices)) in paths.style_groups().into_iter().enumerate() {
+        for i in indices {
+            ids[i] = g;
+        }
+    }
+    ids
+}
+
+/// Returns the key of the grid cell of size `cell` holding `v`.
+fn cell_key(v: Vector, cell: f64) -> (i64, i64) {
+    ((v.x / cell).floor() as i64, (v.y / cell).floor() as i64)
+}
+
+/// A path index, with `true` for the end point of the path.
+type Endpoint = (usize, bool);
+
+/// A hash grid of path endpoints, for finding endpoints within a tolerance.
+struct PointHash {
+    tolerance: f64,
+    cell: f64,
+    cells: HashMap<(usize, i64, i64), Vec<(Vector, Endpoint)>>,
+}
+
+impl PointHash {
+    fn new(tolerance: f64) -> Self {
+        PointHash {
+            tolerance,
+            cell: tolerance.max(1e-9),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, group: usize, v: Vector, item: Endpoint) {
+        let v = flat(v);
+        let (x, y) = cell_key(v, self.cell);
+        self.cells.entry((group, x, y)).or_default().push((v, item));
+    }
+
+    /// Returns the accepted item of `group` nearest to `v`, if one is within
+    /// the tolerance.
+    fn nearest<F: Fn(Endpoint) -> bool>(
+        &self,
+        group: usize,
+        v: Vector,
+        accept: F,
+    ) -> Option<Endpoint> {
+        let v = flat(v);
+        let (cx, cy) = cell_key(v, self.cell);
+        let mut best: Option<(f64, Endpoint)> = None;
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                let Some(cell) = self.cells.get(&(group, x, y)) else {
+                    continue;
+                };
+                for &(p, item) in cell {
+                    let d = v.distance(p);
+                    if d <= self.tolerance && accept(item) && best.is_none_or(|(bd, _)| d < bd) {
+                        best = Some((d, item));
+                    }
+                }
+            }
+        }
+        best.map(|(_, item)| item)
+    }
+}
+
+/// A hash grid of line segments, each stored in every cell it passes
+/// through.
+struct SegmentHash {
+    cell: f64,
+    cells: HashMap<(usize, i64, i64), Vec<(Vector, Vector)>>,
+}
+
+impl SegmentHash {
+    fn new(cell: f64) -> Self {
+        SegmentHash {
+            cell,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, group: usize, a: Vector, b: Vector) {
+        for (x, y) in cells_along(a, b, self.cell) {
+            self.cells.entry((group, x, y)).or_default().push((a, b));
+        }
+    }
+
+    /// Returns the intervals of `a`-`b`, as distances from `a`, that lie
+    /// within `tolerance` of a stored segment of `group`.
+    ///
+    /// Overlaps shorter than half the tolerance (or half the segment, if it
+    /// is shorter) are ignored, so that segments which merely touch at an
+    /// endpoint do not cover each other.
+    fn covered(&self, group: usize, a: Vector, b: Vector, tolerance: f64) -> Vec<(f64, f64)> {
+        let length = a.distance(b);
+        let dir = b.sub(a).mul_scalar(1.0 / length);
+        let min_overlap = 0.5 * tolerance.min(length);
+        let mut result = Vec::new();
+        for (cx, cy) in cells_along(a, b, self.cell) {
+            for x in cx - 1..=cx + 1 {
+                for y in cy - 1..=cy + 1 {
+                    let Some(cell) = self.cells.get(&(group, x, y)) else {
+                        continue;
+                    };
+                    for &(p, q) in cell {
+                        let tp = p.sub(a).dot(dir).clamp(0.0, length);
+                        let tq = q.sub(a).dot(dir).clamp(0.0, length);
+                        let (t0, t1) = (tp.min(tq), tp.max(tq));
+                        if t1 - t0 <= min_overlap {
+                            continue;
+                        }
+                        let u0 = a.add(dir.mul_scalar(t0));
+                        let u1 = a.add(dir.mul_scalar(t1));
+                        if u0.segment_distance(p, q) <= tolerance
+                            && u1.segment_distance(p, q) <= tolerance
+                        {
+                            result.push((t0, t1));
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Returns the keys of the grid cells of size `cell` that the segment
+/// `a`-`b` passes through, in order.
+fn cells_along(a: Vector, b: Vector, cell: f64) -> Vec<(i64, i64)> {
+    let (mut x, mut y) = cell_key(a, cell);
+    let (ex, ey) = cell_key(b, cell);
+    let d = b.sub(a);
+    let step = |d: f64| if d > 0.0 { 1 } else { -1 };
+    let (sx, sy) = (step(d.x), step(d.y));
+    let delta = |d: f64| {
+        if d != 0.0 {
+            cell / d.abs()
+        } else {
+            f64::INFINITY
+        }
+    };
+    let (dx, dy) = (delta(d.x), delta(d.y));
+    let first = |k: i64, s: i64, p: f64, d: f64| {
+        if d == 0.0 {
+            return f64::INFINITY;
+        }
+        let bound = if s > 0 { k + 1 } else { k } as f64 * cell;
+        (bound - p) / d
+    };
+    let mut tx = first(x, sx, a.x, d.x);
+    let mut ty = first(y, sy, a.y, d.y);
+    let steps = (ex - x).abs() + (ey - y).abs();
+    let mut cells = Vec::with_capacity(steps as usize + 1);
+    cells.push((x, y));
+    for _ in 0..steps {
+        if tx < ty {
+            x += sx;
+            tx += dx;
+        } else {
+            y += sy;
+            ty += dy;
+        }
+        cells.push((x, y));
+    }
+    cells
+}
@@ -0,0 +1,298 @@
+//! Vector PDF and Encapsulated PostScript output.
+//!
+//! This module provides [`PageOptions`], which control how
+//! [`Paths::to_pdf`] and [`Paths::to_eps`] place a render on a printed page:
+//! the page size, the margins, and the width and cap style of the lines.
+//!
+//! The render is scaled uniformly to fit inside the margins and centred on
+//! the page. To embed a render in a document without surrounding space, use
+//! a page with the aspect ratio of the render and no margins.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Cube, LineCap, PageOptions, Scene, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+//!
+//! let paths = scene.render(
+//!     Vector::new(4.0, 3.0, 2.0),
+//!     Vector::new(0.0, 0.0, 0.0),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     1024.0, 1024.0, 50.0, 0.1, 10.0, 0.01,
+//! );
+//!
+//! paths.write_to_pdf("output.pdf", 1024.0, 1024.0, &PageOptions::new()).unwrap();
+//!
+//! // An 80 mm square figure for LaTeX.
+//! let figure = PageOptions::new()
+//!     .with_page_size(80.0, 80.0)
+//!     .with_margin(0.0)
+//!     .with_line_cap(LineCap::Butt);
+//! paths.write_to_eps("output.eps", 1024.0, 1024.0, &figure).unwrap();
+//! ```
+
+use crate::path::Paths;
+use crate::vector::Vector;
+
+/// PostScript points per millimetre.
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+/// The shape of the ends of lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Lines end squarely at their endpoints.
+    Butt,
+    /// Lines end in a semicircle, with round joins between segments.
+    #[default]
+    Round,
+    /// Lines end in a square extending half the line width past their
+    /// endpoints.
+    Square,
+}
+
+impl LineCap {
+    /// Returns the PDF and PostScript line cap and line join codes.
+    fn codes(self) -> (u8, u8) {
+        match self {
+            LineCap::Butt => (0, 0),
+            LineCap::Round => (1, 1),
+            LineCap::Square => (2, 0),
+        }
+    }
+}
+
+/// Page layout options for PDF and EPS output.
+///
+/// All lengths are in millimetres.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{LineCap, PageOptions};
+///
+/// let options = PageOptions::new().with_page_size(297.0, 210.0).with_line_width(0.5);
+/// assert_eq!(options.margin, 10.0);
+/// assert_eq!(options.line_cap, LineCap::Round);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageOptions {
+    /// The page width.
+    pub page_width: f64,
+    /// The page height.
+    pub page_height: f64,
+    /// The space left empty on each side of the page.
+    pub margin: f64,
+    /// The width of the lines.
+    pub line_width: f64,
+    /// The shape of the ends of lines.
+    pub line_cap: LineCap,
+}
+
+impl PageOptions {
+    /// Creates the default options: a portrait A4 page with 10 mm margins
+    /// and round-capped lines 0.25 mm wide.
+    pub fn new() -> Self {
+        PageOptions {
+            page_width: 210.0,
+            page_height: 297.0,
+            margin: 10.0,
+            line_width: 0.25,
+            line_cap: LineCap::Round,
+        }
+    }
+
+    /// Sets the page size.
+    pub fn with_page_size(mut self, width: f64, height: f64) -> Self {
+        self.page_width = width;
+        self.page_height = height;
+        self
+    }
+
+    /// Sets the margin on each side of the page.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the line width.
+    pub fn with_line_width(mut self, line_width: f64) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Sets the shape of the ends of lines.
+    pub fn with_line_cap(mut self, line_cap: LineCap) -> Self {
+        self.line_cap = line_cap;
+        self
+    }
+
+    /// Returns the page size in points.
+    fn page_points(&self) -> (f64, f64) {
+        (
+            self.page_width * POINTS_PER_MM,
+            self.page_height * POINTS_PER_MM,
+        )
+    }
+
+    /// Returns a function mapping a point of a `width` x `height` render to
+    /// page coordinates in points.
+    fn placement(&self, width: f64, height: f64) -> impl Fn(Vector) -> (f64, f64) {
+        let (page_width, page_height) = self.page_points();
+        let margin = self.margin * POINTS_PER_MM;
+        let scale =
+            ((page_width - 2.0 * margin) / width).min((page_height - 2.0 * margin) / height);
+        let x0 = (page_width - width * scale) / 2.0;
+        let y0 = (page_height - height * scale) / 2.0;
+        move |v| (x0 + v.x * scale, y0 + v.y * scale)
+    }
+}
+
+impl Default for PageOptions {
+    fn default() -> Self {
+        PageOptions::new()
+    }
+}
+
+/// Returns the drawing operators shared by PDF and PostScript, using the
+/// given operator names for move to, line to and stroke.
+fn drawing(
+    paths: &Paths,
+    width: f64,
+    height: f64,
+    options: &PageOptions,
+    ops: [&str; 3],
+) -> Vec<String> {
+    let place = options.placement(width, height);
+    let mut lines = Vec::new();
+    for path in paths.paths.iter().filter(|p| !p.is_empty()) {
+        let points: Vec<(f64, f64)> = path.iter().map(|v| place(*v)).collect();
+        let (x, y) = points[0];
+        lines.push(format!("{} {} {}", number(x), number(y), ops[0]));
+        // A single point is drawn as a dot, by a line back to itself.
+        let rest = if points.len() == 1 {
+            &points[..]
+        } else {
+            &points[1..]
+        };
+        for &(x, y) in rest {
+            lines.push(format!("{} {} {}", number(x), number(y), ops[1]));
+        }
+        lines.push(ops[2].to_string());
+    }
+    lines
+}
+
+/// Converts `paths`, rendered at `width` x `height`, to a single-page PDF
+/// file.
+pub(crate) fn paths_to_pdf(
+    paths: &Paths,
+    width: f64,
+    height: f64,
+    options: &PageOptions,
+) -> Vec<u8> {
+    let (page_width, page_height) = options.page_points();
+    let (cap, join) = options.line_cap.codes();
+    let mut content = vec![format!(
+        "{} w {} J {} j",
+        number(options.line_width * POINTS_PER_MM),
+        cap,
+        join
+    )];
+    content.extend(drawing(paths, width, height, options, ["m", "l", "S"]));
+    let content = content.join("\n");
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << >> /Contents 4 0 R >>",
+            number(page_width),
+            number(page_height)
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        ),
+    ];
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .bytes(),
+    );
+    pdf
+}
+
+/// Converts `paths`, rendered at `width` x `height`, to an Encapsulated
+/// PostScript file whose bounding box is the page.
+pub(crate) fn paths_to_eps(
+    paths: &Paths,
+    width: f64,
+    height: f64,
+    options: &PageOptions,
+) -> String {
+    let (page_width, page_height) = options.page_points();
+    let (cap, join) = options.line_cap.codes();
+    let mut lines = vec![
+        "%!PS-Adobe-3.0 EPSF-3.0".to_string(),
+        format!(
+            "%%BoundingBox: 0 0 {} {}",
+            page_width.ceil(),
+            page_height.ceil()
+        ),
+        format!(
+            "%%HiResBoundingBox: 0 0 {} {}",
+            number(page_width),
+            number(page_height)
+        ),
+        "%%Creator: larnt".to_string(),
+        "%%Pages: 1".to_string(),
+        "%%EndComments".to_string(),
+        format!(
+            "{} setlinewidth {} setlinecap {} setlinejoin",
+            number(options.line_width * POINTS_PER_MM),
+            cap,
+            join
+        ),
+    ];
+    lines.extend(drawing(
+        paths,
+        width,
+        height,
+        options,
+        ["newpath moveto", "lineto", "stroke"],
+    ));
+    lines.push("showpage".to_string());
+    lines.push("%%EOF".to_string());
+    let mut eps = lines.join("\n");
+    eps.push('\n');
+    eps
+}
+
+/// Formats a number with at most three decimal places and no trailing
+/// zeros.
+fn number(x: f64) -> String {
+    let s = format!("{:.3}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" => "0".to_string(),
+        _ => s.to_string(),
+    }
+}
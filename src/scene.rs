@@ -22,16 +22,232 @@
 //! paths.write_to_png("output.png", 1024.0, 1024.0);
 //! ```
 
-use crate::filter::ClipFilter;
+use crate::camera::{Camera, ViewInfo};
+use crate::filter::{ClipFilter, Filter, ViewFilter, VisibilitySplit};
+use crate::hatch::{hatch, Hatching};
 use crate::hit::Hit;
 use crate::matrix::Matrix;
 use crate::path::Paths;
+use crate::plane::Plane;
 use crate::ray::Ray;
 use crate::shape::Shape;
+use crate::style::Style;
 use crate::tree::Tree;
 use crate::vector::Vector;
 use std::sync::Arc;
 
+/// Options controlling how a [`Scene`] is rendered.
+///
+/// These are the settings that are independent of the [`Camera`].
+///
+/// # Example
+///
+/// ```
+/// use larnt::RenderOptions;
+///
+/// let options = RenderOptions::new().with_step(0.05);
+/// assert_eq!(options.step, 0.05);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Path subdivision step size for visibility testing.
+    ///
+    /// A step of `0.0` or less disables chopping, so only the original path
+    /// vertices are tested.
+    pub step: f64,
+    /// The units `step` is measured in.
+    pub step_units: StepUnits,
+    /// How the visibility of the chopped paths is resolved.
+    pub visibility: Visibility,
+    /// Light-direction hatch shading, if any.
+    pub hatching: Option<Hatching>,
+}
+
+impl RenderOptions {
+    /// Creates the default render options (a step of `0.01` world units
+    /// with [`Visibility::Sampled`]).
+    pub fn new() -> Self {
+        RenderOptions {
+            step: 0.01,
+            step_units: StepUnits::World,
+            visibility: Visibility::Sampled,
+            hatching: None,
+        }
+    }
+
+    /// Sets the path subdivision step size, in world units.
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self.step_units = StepUnits::World;
+        self
+    }
+
+    /// Sets the path subdivision step size, in output pixels.
+    ///
+    /// Each segment is subdivided according to its projected length, so
+    /// the precision of the visibility test does not depend on the scale of
+    /// the scene or the distance to the camera.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{RenderOptions, StepUnits};
+    ///
+    /// let options = RenderOptions::new().with_pixel_step(2.0);
+    /// assert_eq!(options.step_units, StepUnits::Pixels);
+    /// ```
+    pub fn with_pixel_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self.step_units = StepUnits::Pixels;
+        self
+    }
+
+    /// Sets the visibility mode.
+    ///
+    /// A [`Visibility::Bisect`] tolerance below [`MIN_BISECT_TOLERANCE`] is
+    /// raised to it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{RenderOptions, Visibility, MIN_BISECT_TOLERANCE};
+    ///
+    /// let options = RenderOptions::new().with_visibility(Visibility::Bisect { tolerance: 0.0 });
+    /// assert_eq!(options.visibility, Visibility::Bisect { tolerance: MIN_BISECT_TOLERANCE });
+    /// ```
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = match visibility {
+            Visibility::Bisect { tolerance } => Visibility::Bisect {
+                tolerance: tolerance.max(MIN_BISECT_TOLERANCE),
+            },
+            v => v,
+        };
+        self
+    }
+
+    /// Enables hatch shading from a directional light.
+    ///
+    /// The hatch lines are added to the visible output of the render.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Hatching, RenderOptions, Vector};
+    ///
+    /// let options = RenderOptions::new()
+    ///     .with_hatching(Hatching::new(Vector::new(0.0, 0.0, 1.0)));
+    /// assert!(options.hatching.is_some());
+    /// ```
+    pub fn with_hatching(mut self, hatching: Hatching) -> Self {
+        self.hatching = Some(hatching);
+        self
+    }
+}
+
+/// Units of the path subdivision step in [`RenderOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StepUnits {
+    /// The step is a distance in world space.
+    #[default]
+    World,
+    /// The step is a distance in output pixels, measured after projection.
+    Pixels,
+}
+
+/// Smallest tolerance [`RenderOptions::with_visibility`] accepts for
+/// [`Visibility::Bisect`].
+pub const MIN_BISECT_TOLERANCE: f64 = 1e-9;
+
+/// Distance, as a fraction of the scene's size, below which
+/// [`Scene::intersect`] skips hits when clip planes are set.
+const CLIP_EPS: f64 = 1e-6;
+
+/// How path visibility is resolved during rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Visibility {
+    /// Every chopped point is tested and hidden points are dropped.
+    ///
+    /// Occlusion boundaries are only as precise as the chop step.
+    #[default]
+    Sampled,
+    /// Only the chopped points are tested, and segments whose visibility
+    /// changes are bisected until the boundary is within `tolerance`.
+    ///
+    /// This gives exact occlusion boundaries with a coarse step, using far
+    /// fewer ray casts than a fine step would. Occluders smaller than the
+    /// step may be missed.
+    Bisect {
+        /// Maximum distance between the located boundary and the true one.
+        /// Should be positive; see [`RenderOptions::with_visibility`].
+        tolerance: f64,
+    },
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions::new()
+    }
+}
+
+/// A shape drawn over a scene without occluding anything, such as an axis,
+/// a guide line or a label.
+///
+/// Annotations are kept out of the scene's BVH tree, so they never block
+/// the view of other paths. By default an annotation's own paths are still
+/// hidden by solid geometry; [`with_on_top`](Annotation::with_on_top) skips
+/// the visibility test for them entirely. Clip planes do not cut
+/// annotations.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{Annotation, Scene, Sphere, Style, Vector};
+///
+/// let mut scene = Scene::new();
+/// scene.add_annotation(
+///     Annotation::new(Sphere::new(Vector::default(), 1.0))
+///         .with_on_top(true)
+///         .with_style(Style::new().with_color("blue")),
+/// );
+/// ```
+pub struct Annotation {
+    /// The annotation shape.
+    pub shape: Arc<dyn Shape + Send + Sync>,
+    /// Whether the paths are drawn even where solid geometry hides them.
+    pub on_top: bool,
+    /// The style of the annotation's paths, if any.
+    pub style: Option<Arc<Style>>,
+}
+
+impl Annotation {
+    /// Creates an annotation that is hidden by solid geometry.
+    pub fn new<S: Shape + Send + Sync + 'static>(mut shape: S) -> Self {
+        shape.compile();
+        Annotation::from_arc(Arc::new(shape))
+    }
+
+    /// Creates an annotation from a pre-wrapped shape.
+    pub fn from_arc(shape: Arc<dyn Shape + Send + Sync>) -> Self {
+        Annotation {
+            shape,
+            on_top: false,
+            style: None,
+        }
+    }
+
+    /// Sets whether the annotation is drawn on top of solid geometry.
+    pub fn with_on_top(mut self, on_top: bool) -> Self {
+        self.on_top = on_top;
+        self
+    }
+
+    /// Sets the style of the annotation's paths.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(Arc::new(style));
+        self
+    }
+}
+
 /// A container for 3D shapes that handles rendering.
 ///
 /// The `Scene` struct collects shapes and provides methods to render them
@@ -59,8 +275,14 @@ use std::sync::Arc;
 pub struct Scene {
     /// The shapes in this scene.
     pub shapes: Vec<Arc<dyn Shape + Send + Sync>>,
+    /// The style of each shape, by index. Shapes without an entry are unstyled.
+    pub styles: Vec<Option<Arc<Style>>>,
     /// The BVH tree for efficient intersection testing.
     pub tree: Option<Tree>,
+    /// Planes cutting the scene away. See [`Scene::add_clip_plane`].
+    pub clip_planes: Vec<Plane>,
+    /// Shapes drawn without occluding anything. See [`Scene::add_annotation`].
+    pub annotations: Vec<Annotation>,
 }
 
 impl Scene {
@@ -68,7 +290,10 @@ impl Scene {
     pub fn new() -> Self {
         Scene {
             shapes: Vec::new(),
+            styles: Vec::new(),
             tree: None,
+            clip_planes: Vec::new(),
+            annotations: Vec::new(),
         }
     }
 
@@ -87,6 +312,11 @@ impl Scene {
                 s.compile();
             }
         }
+        for annotation in &mut self.annotations {
+            if let Some(s) = Arc::get_mut(&mut annotation.shape) {
+                s.compile();
+            }
+        }
         if self.tree.is_none() {
             self.tree = Some(Tree::new(self.shapes.clone()));
         }
@@ -128,14 +358,166 @@ impl Scene {
         self.shapes.push(shape);
     }
 
+    /// Adds a shape to the scene with a stroke [`Style`].
+    ///
+    /// The style is attached to every path the shape produces and is
+    /// written out by [`Paths::to_svg`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Scene, Sphere, Style, Vector};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.add_styled(
+    ///     Sphere::new(Vector::default(), 1.0),
+    ///     Style::new().with_color("red").with_width(2.0),
+    /// );
+    /// ```
+    pub fn add_styled<S: Shape + Send + Sync + 'static>(&mut self, mut shape: S, style: Style) {
+        shape.compile();
+        self.add_arc_styled(Arc::new(shape), style);
+    }
+
+    /// Adds a pre-wrapped shape to the scene with a stroke [`Style`].
+    pub fn add_arc_styled(&mut self, shape: Arc<dyn Shape + Send + Sync>, style: Style) {
+        self.styles.resize(self.shapes.len(), None);
+        self.styles.push(Some(Arc::new(style)));
+        self.shapes.push(shape);
+    }
+
+    /// Adds a shape that is drawn but never hides anything.
+    ///
+    /// Its paths are still hidden by solid geometry. Use
+    /// [`Scene::add_on_top`] to draw them regardless.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Cube, Cylinder, Scene, Vector};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+    /// // A thin axis through the cube that does not hide its far edges.
+    /// scene.add_draw_only(Cylinder::new(0.01, -2.0, 2.0));
+    /// ```
+    pub fn add_draw_only<S: Shape + Send + Sync + 'static>(&mut self, shape: S) {
+        self.add_annotation(Annotation::new(shape));
+    }
+
+    /// Adds a shape that is drawn on top of everything, skipping the
+    /// visibility test for its paths.
+    pub fn add_on_top<S: Shape + Send + Sync + 'static>(&mut self, shape: S) {
+        self.add_annotation(Annotation::new(shape).with_on_top(true));
+    }
+
+    /// Adds an [`Annotation`].
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        self.annotations.push(annotation);
+    }
+
+    /// Adds a clip plane for a cut-away view.
+    ///
+    /// Everything on the side the plane's normal points to is removed, both
+    /// from the path output and as an occluder. Where the plane cuts a
+    /// solid, the outline of the section is drawn with the shape's style and
+    /// the cut face hides what lies behind it.
+    ///
+    /// Sections are traced from [`Shape::section`]. Shapes whose
+    /// [`contains`](Shape::contains) always returns `false` are cut open
+    /// rather than capped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{Plane, Scene, Sphere, Vector};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.add(Sphere::new(Vector::default(), 1.0));
+    /// // Remove the half of the sphere facing +x.
+    /// scene.add_clip_plane(Plane::new(Vector::default(), Vector::new(1.0, 0.0, 0.0)));
+    ///
+    /// let paths = scene.paths();
+    /// assert!(paths.paths.iter().flatten().all(|v| v.x <= 1e-9));
+    /// ```
+    pub fn add_clip_plane(&mut self, plane: Plane) {
+        self.clip_planes.push(plane);
+    }
+
     /// Tests for ray-scene intersection.
     ///
     /// Returns a [`Hit`] describing the intersection, or [`Hit::no_hit()`]
     /// if the ray doesn't hit any shape.
+    ///
+    /// Hits on geometry removed by a clip plane are skipped, and the cut
+    /// faces of solids are hit as well.
     pub fn intersect(&self, r: Ray) -> Hit {
-        self.tree
-            .as_ref()
-            .map_or(Hit::no_hit(), |tree| tree.intersect(r))
+        let Some(tree) = self.tree.as_ref() else {
+            return Hit::no_hit();
+        };
+        if self.clip_planes.is_empty() {
+            return tree.intersect(r);
+        }
+
+        // Hits closer than this are skipped: it steps past removed geometry
+        // and keeps points on a cut face from hiding themselves. It scales
+        // with the scene so that tiny and huge scenes behave alike.
+        let size = tree.bx.size().length();
+        let eps = if size.is_finite() && size > 0.0 {
+            size * CLIP_EPS
+        } else {
+            CLIP_EPS
+        };
+
+        let mut result = Hit::no_hit();
+        let mut t0 = 0.0;
+        loop {
+            let hit = tree.intersect(Ray::new(r.position(t0), r.direction));
+            if !hit.is_ok() {
+                break;
+            }
+            let t = t0 + hit.t;
+            if self.clip_kept(r.position(t), None) {
+                result = Hit { t, ..hit };
+                break;
+            }
+            t0 = t + eps;
+        }
+
+        for (i, plane) in self.clip_planes.iter().enumerate() {
+            let Some(t) = plane.intersect_ray(r) else {
+                continue;
+            };
+            if t < eps || (result.is_ok() && t >= result.t) {
+                continue;
+            }
+            let v = r.position(t);
+            if !self.clip_kept(v, Some(i)) {
+                continue;
+            }
+            if let Some(shape) = self.solid_at(v) {
+                result = Hit::new(t)
+                    .with_surface(v, plane.normal.normalize())
+                    .with_shape(shape);
+            }
+        }
+        if result.is_ok() {
+            result.position = r.position(result.t);
+        }
+        result
+    }
+
+    /// Tests if `v` is kept by every clip plane, optionally ignoring one.
+    fn clip_kept(&self, v: Vector, skip: Option<usize>) -> bool {
+        self.clip_planes
+            .iter()
+            .enumerate()
+            .all(|(i, plane)| Some(i) == skip || plane.signed_distance(v) <= 0.0)
+    }
+
+    /// Returns the index of a shape of the scene that contains `v`.
+    fn solid_at(&self, v: Vector) -> Option<usize> {
+        self.tree.as_ref()?.shape_containing(v, 0.0)
     }
 
     /// Tests if a point is visible from the camera position.
@@ -153,10 +535,81 @@ impl Scene {
     }
 
     /// Returns all paths from all shapes in the scene.
+    ///
+    /// Paths of styled shapes carry the shape's style. With clip planes,
+    /// the paths are cut at the planes and the section outlines are added.
+    /// The paths of annotations come last.
     pub fn paths(&self) -> Paths {
+        self.all_paths(None)
+    }
+
+    /// Returns all paths from all shapes in the scene as seen from `view`.
+    ///
+    /// This is the same as [`Scene::paths`], except that shapes whose paths
+    /// depend on the viewpoint, such as silhouettes, are drawn for `view`
+    /// (see [`Shape::paths_for_view`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use larnt::{OutlineSphere, Scene, Vector, ViewInfo};
+    ///
+    /// let up = Vector::new(0.0, 0.0, 1.0);
+    /// let mut scene = Scene::new();
+    /// scene.add(OutlineSphere::new(Vector::new(4.0, 0.0, 0.0), up, Vector::default(), 1.0));
+    ///
+    /// let view = ViewInfo::new(Vector::new(0.0, 4.0, 0.0), up);
+    /// let paths = scene.paths_for_view(&view);
+    /// // The silhouette circle now faces the new eye.
+    /// assert!(paths.paths.iter().flatten().all(|p| (p.y - 0.25).abs() < 1e-9));
+    /// ```
+    pub fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.all_paths(Some(view))
+    }
+
+    fn all_paths(&self, view: Option<&ViewInfo>) -> Paths {
+        let mut result = self.solid_paths(view);
+        result.extend(self.annotation_paths(false, view));
+        result.extend(self.annotation_paths(true, view));
+        result
+    }
+
+    /// Returns the paths of the annotations drawn on top, or of the others.
+    fn annotation_paths(&self, on_top: bool, view: Option<&ViewInfo>) -> Paths {
+        let mut result = Paths::new();
+        for annotation in self.annotations.iter().filter(|a| a.on_top == on_top) {
+            let paths = shape_paths(annotation.shape.as_ref(), view);
+            match &annotation.style {
+                Some(style) => result.extend(paths.with_style_arc(style.clone())),
+                None => result.extend(paths),
+            }
+        }
+        result
+    }
+
+    /// Returns the paths of the shapes, cut by the clip planes.
+    fn solid_paths(&self, view: Option<&ViewInfo>) -> Paths {
         let mut result = Paths::new();
-        for shape in &self.shapes {
-            result.extend(shape.paths());
+        for (i, shape) in self.shapes.iter().enumerate() {
+            let mut paths = shape_paths(shape.as_ref(), view);
+            if !self.clip_planes.is_empty() {
+                for plane in &self.clip_planes {
+                    paths = plane.clip_paths(&paths);
+                }
+                for (j, plane) in self.clip_planes.iter().enumerate() {
+                    let mut section = shape.section(plane);
+                    for (k, other) in self.clip_planes.iter().enumerate() {
+                        if k != j {
+                            section = other.clip_paths(&section);
+                        }
+                    }
+                    paths.extend(section);
+                }
+            }
+            match self.styles.get(i).cloned().flatten() {
+                Some(style) => result.extend(paths.with_style_arc(style)),
+                None => result.extend(paths),
+            }
         }
         result
     }
@@ -182,6 +635,9 @@ impl Scene {
     /// * `far` - Far clipping plane distance
     /// * `step` - Path subdivision step size for visibility testing
     ///
+    /// See [`Scene::render_camera`] for a version that takes a [`Camera`]
+    /// instead of positional arguments.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -198,6 +654,7 @@ impl Scene {
     ///     50.0, 0.1, 10.0, 0.01,
     /// );
     /// ```
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
         eye: Vector,
@@ -210,16 +667,54 @@ impl Scene {
         far: f64,
         step: f64,
     ) -> Paths {
-        let aspect = width / height;
-        let matrix = Matrix::look_at(eye, center, up);
-        let matrix = matrix.with_perspective(fovy, aspect, near, far);
-        self.render_with_matrix(matrix, eye, width, height, step)
+        let camera = Camera::perspective(eye, center, up, fovy)
+            .with_viewport(width, height)
+            .with_clip(near, far);
+        self.render_camera(&camera, &RenderOptions::new().with_step(step))
+    }
+
+    /// Renders the scene as seen by a [`Camera`].
+    ///
+    /// The output is sized to the camera's viewport.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use larnt::{Camera, Cube, RenderOptions, Scene, Vector};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+    ///
+    /// let camera = Camera::perspective(
+    ///     Vector::new(4.0, 3.0, 2.0),
+    ///     Vector::new(0.0, 0.0, 0.0),
+    ///     Vector::new(0.0, 0.0, 1.0),
+    ///     50.0,
+    /// )
+    /// .with_clip(0.1, 10.0);
+    ///
+    /// let paths = scene.render_camera(&camera, &RenderOptions::new().with_step(0.01));
+    /// ```
+    pub fn render_camera(&mut self, camera: &Camera, options: &RenderOptions) -> Paths {
+        self.render_matrix(
+            camera.matrix(),
+            camera.view(),
+            camera.width,
+            camera.height,
+            options,
+        )
     }
 
     /// Renders the scene with a custom transformation matrix.
     ///
     /// This gives you full control over the projection matrix, useful for
     /// orthographic projections or custom camera setups.
+    ///
+    /// With the `parallel` feature enabled, the visibility test is run on
+    /// multiple threads. The output is the same as the serial version.
+    ///
+    /// View-dependent paths are drawn from `eye`, with the up direction
+    /// taken from the vertical axis of the screen.
     pub fn render_with_matrix(
         &mut self,
         matrix: Matrix,
@@ -228,35 +723,177 @@ impl Scene {
         height: f64,
         step: f64,
     ) -> Paths {
-        self.compile();
-        let mut paths = self.paths();
+        let options = RenderOptions::new().with_step(step);
+        let inverse = matrix.inverse();
+        let bottom = inverse.mul_position_w(Vector::new(0.0, -1.0, 0.0));
+        let top = inverse.mul_position_w(Vector::new(0.0, 1.0, 0.0));
+        let view = ViewInfo::new(eye, (top - bottom).normalize())
+            .with_matrix(matrix)
+            .with_viewport(width, height);
+        self.render_matrix(matrix, view, width, height, &options)
+    }
 
-        if step > 0.0 {
-            paths = paths.chop(step);
-        }
+    /// Renders the scene as seen by a [`Camera`], returning the visible and
+    /// the hidden paths separately.
+    ///
+    /// The first set is the same as [`Scene::render_camera`] returns. The
+    /// second holds the portions of paths that are inside the view but
+    /// occluded by other geometry, which technical illustrations usually
+    /// draw dashed. Both sets keep the shapes' styles.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use larnt::{Camera, Cube, RenderOptions, Scene, Style, Vector};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+    ///
+    /// let camera = Camera::perspective(
+    ///     Vector::new(4.0, 3.0, 2.0),
+    ///     Vector::new(0.0, 0.0, 0.0),
+    ///     Vector::new(0.0, 0.0, 1.0),
+    ///     50.0,
+    /// );
+    ///
+    /// let (mut paths, hidden) = scene.render_with_hidden(&camera, &RenderOptions::default());
+    /// paths.extend(hidden.with_style(Style::new().with_color("gray").with_dash(vec![6.0, 4.0])));
+    /// paths.write_to_svg("output.svg", camera.width, camera.height).unwrap();
+    /// ```
+    pub fn render_with_hidden(
+        &mut self,
+        camera: &Camera,
+        options: &RenderOptions,
+    ) -> (Paths, Paths) {
+        let matrix = camera.matrix();
+        let (eye, width, height) = (camera.eye, camera.width, camera.height);
+        let (paths, on_top) = self.chopped_paths(&matrix, &camera.view(), width, height, options);
 
-        let filter = ClipFilter {
+        let split = VisibilitySplit {
             matrix,
             eye,
             scene: self,
         };
-        paths = paths.filter(&filter);
+        let (visible, hidden) = partition_paths(&paths, &split, options.visibility);
+
+        let mut visible = to_screen(visible, width, height, options);
+        if let Some(hatching) = &options.hatching {
+            visible.extend(hatch(self, &matrix, width, height, hatching));
+        }
+        let filter = ViewFilter { matrix };
+        let on_top = filter_paths(&on_top, &filter, options.visibility);
+        visible.extend(to_screen(on_top, width, height, options));
+        (visible, to_screen(hidden, width, height, options))
+    }
 
-        if step > 0.0 {
-            paths = paths.simplify(1e-6);
+    fn render_matrix(
+        &mut self,
+        matrix: Matrix,
+        view: ViewInfo,
+        width: f64,
+        height: f64,
+        options: &RenderOptions,
+    ) -> Paths {
+        let (paths, on_top) = self.chopped_paths(&matrix, &view, width, height, options);
+        let filter = ClipFilter {
+            matrix,
+            eye: view.eye,
+            scene: self,
+        };
+        let paths = filter_paths(&paths, &filter, options.visibility);
+        let mut result = to_screen(paths, width, height, options);
+        if let Some(hatching) = &options.hatching {
+            result.extend(hatch(self, &matrix, width, height, hatching));
         }
+        let filter = ViewFilter { matrix };
+        let on_top = filter_paths(&on_top, &filter, options.visibility);
+        result.extend(to_screen(on_top, width, height, options));
+        result
+    }
+
+    /// Compiles the scene and returns its paths, chopped for visibility
+    /// testing: first those that are tested, then those drawn on top.
+    fn chopped_paths(
+        &mut self,
+        matrix: &Matrix,
+        view: &ViewInfo,
+        width: f64,
+        height: f64,
+        options: &RenderOptions,
+    ) -> (Paths, Paths) {
+        self.compile();
+        let view = Some(view);
+        let mut paths = self.solid_paths(view);
+        paths.extend(self.annotation_paths(false, view));
+        let on_top = self.annotation_paths(true, view);
 
-        let matrix = Matrix::translate(Vector::new(1.0, 1.0, 0.0)).scaled(Vector::new(
-            width / 2.0,
-            height / 2.0,
-            0.0,
-        ));
-        paths = paths.transform(&matrix);
+        let step = options.step;
+        let chop = |paths: Paths| {
+            if step <= 0.0 {
+                return paths;
+            }
+            match options.step_units {
+                StepUnits::World => paths.chop(step),
+                StepUnits::Pixels => paths.chop_projected(matrix, width, height, step),
+            }
+        };
+        (chop(paths), chop(on_top))
+    }
+}
 
-        paths
+fn shape_paths(shape: &(dyn Shape + Send + Sync), view: Option<&ViewInfo>) -> Paths {
+    match view {
+        Some(view) => shape.paths_for_view(view),
+        None => shape.paths(),
     }
 }
 
+fn filter_paths<F: Filter + Sync>(paths: &Paths, filter: &F, visibility: Visibility) -> Paths {
+    match visibility {
+        #[cfg(feature = "parallel")]
+        Visibility::Sampled => paths.par_filter(filter),
+        #[cfg(not(feature = "parallel"))]
+        Visibility::Sampled => paths.filter(filter),
+        #[cfg(feature = "parallel")]
+        Visibility::Bisect { tolerance } => paths.par_filter_bisect(filter, tolerance),
+        #[cfg(not(feature = "parallel"))]
+        Visibility::Bisect { tolerance } => paths.filter_bisect(filter, tolerance),
+    }
+}
+
+/// Splits paths into their visible and hidden parts, testing each point
+/// once.
+fn partition_paths(
+    paths: &Paths,
+    split: &VisibilitySplit,
+    visibility: Visibility,
+) -> (Paths, Paths) {
+    let classify = |v| split.classify(v);
+    let tolerance = match visibility {
+        Visibility::Sampled => None,
+        Visibility::Bisect { tolerance } => Some(tolerance),
+    };
+    #[cfg(feature = "parallel")]
+    let parts = paths.par_partition(&classify, tolerance);
+    #[cfg(not(feature = "parallel"))]
+    let parts = paths.partition(&classify, tolerance);
+    parts
+}
+
+/// Simplifies filtered paths and maps them from clip space to the viewport.
+fn to_screen(mut paths: Paths, width: f64, height: f64, options: &RenderOptions) -> Paths {
+    if options.step > 0.0 {
+        paths = paths.simplify(1e-6);
+    }
+
+    let matrix = Matrix::translate(Vector::new(1.0, 1.0, 0.0)).scaled(Vector::new(
+        width / 2.0,
+        height / 2.0,
+        0.0,
+    ));
+    paths.transform(&matrix)
+}
+
 impl Default for Scene {
     fn default() -> Self {
         Scene::new()
@@ -0,0 +1,514 @@
+//! Signed distance field shapes.
+//!
+//! This module provides the [`SdfShape`] shape, a solid given implicitly by
+//! a signed distance function: negative inside, positive outside and zero
+//! on the surface. Rays are intersected by sphere tracing, and the surface
+//! is drawn with isocontours, the curves where it meets a stack of parallel
+//! slicing planes.
+//!
+//! Distance functions are plain closures. The functions in this module build
+//! common primitives ([`sphere`], [`cuboid`], [`torus`]) and combine them
+//! ([`union`], [`intersection`], [`difference`], [`smooth_union`],
+//! [`blend`], [`twist`], [`round`]).
+//!
+//! # Example
+//!
+//! ```
+//! use larnt::sdf::{self, SdfShape};
+//! use larnt::{Box, Scene, Vector};
+//!
+//! // Two spheres melted together
+//! let func = sdf::smooth_union(
+//!     sdf::sphere(Vector::new(-0.6, 0.0, 0.0), 0.8),
+//!     sdf::sphere(Vector::new(0.6, 0.0, 0.0), 0.8),
+//!     0.4,
+//! );
+//! let bx = Box::new(Vector::new(-1.5, -1.0, -1.0), Vector::new(1.5, 1.0, 1.0));
+//!
+//! let mut scene = Scene::new();
+//! scene.add(SdfShape::new(func, bx).with_slices(32));
+//! ```
+
+use crate::bounding_box::Box;
+use crate::hit::Hit;
+use crate::path::Paths;
+use crate::ray::Ray;
+use crate::shape::Shape;
+use crate::vector::Vector;
+
+/// Largest number of sphere tracing steps along a ray.
+const MAX_STEPS: usize = 1024;
+
+/// Distance below which sphere tracing stops at the surface.
+const HIT_EPSILON: f64 = 1e-5;
+
+/// Number of bisection steps used to refine each contour crossing.
+const CONTOUR_REFINE: usize = 12;
+
+/// Texture style for SdfShape shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SdfTexture {
+    /// Isocontours on planes perpendicular to the slice normal (default)
+    #[default]
+    Contours,
+    /// Isocontours on planes perpendicular to each of the X, Y and Z axes
+    CrossContours,
+}
+
+/// A solid given by a signed distance function.
+///
+/// The function must not overestimate the distance to the surface by more
+/// than the [`lipschitz`](SdfShape::lipschitz) factor; sphere tracing steps by the distance
+/// divided by this factor. Exact distance functions, and the results of
+/// [`union`], [`intersection`], [`smooth_union`] and [`round`], need no
+/// correction. Deformations such as [`twist`] stretch space and need a
+/// larger factor.
+///
+/// The solid is limited to the bounding box `bx`; anything outside it is cut
+/// away.
+///
+/// # Example
+///
+/// ```
+/// use larnt::sdf::{self, SdfShape};
+/// use larnt::{Box, Ray, Shape, Vector};
+///
+/// let bx = Box::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0));
+/// let ball = SdfShape::new(sdf::sphere(Vector::new(0.0, 0.0, 0.0), 1.0), bx);
+///
+/// assert!(ball.contains(Vector::new(0.0, 0.5, 0.0), 0.0));
+/// assert!(!ball.contains(Vector::new(0.8, 0.8, 0.0), 0.0));
+///
+/// let hit = ball.intersect(Ray::new(Vector::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0)));
+/// assert!((hit.t - 4.0).abs() < 1e-4);
+/// ```
+pub struct SdfShape<F>
+where
+    F: Fn(Vector) -> f64 + Send + Sync,
+{
+    /// The signed distance function.
+    pub func: F,
+    /// The bounding box of the solid.
+    pub bx: Box,
+    /// The texture style for the shape.
+    pub texture: SdfTexture,
+    /// The normal of the slicing planes for [`SdfTexture::Contours`].
+    pub slice_normal: Vector,
+    /// The number of slicing planes along each slicing direction.
+    pub slices: usize,
+    /// The number of grid cells across each slice used to trace contours.
+    pub resolution: usize,
+    /// How much the function may overestimate the distance to the surface.
+    /// Always positive, so it is only set through `with_lipschitz`.
+    lipschitz: f64,
+}
+
+impl<F> SdfShape<F>
+where
+    F: Fn(Vector) -> f64 + Send + Sync,
+{
+    /// Creates a solid from a signed distance function and a bounding box,
+    /// with 16 horizontal contours.
+    pub fn new(func: F, bx: Box) -> Self {
+        SdfShape {
+            func,
+            bx,
+            texture: SdfTexture::default(),
+            slice_normal: Vector::new(0.0, 0.0, 1.0),
+            slices: 16,
+            resolution: 128,
+            lipschitz: 1.0,
+        }
+    }
+
+    /// Sets the texture style for the shape.
+    pub fn with_texture(mut self, texture: SdfTexture) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Sets the number of slicing planes along each slicing direction.
+    pub fn with_slices(mut self, slices: usize) -> Self {
+        self.slices = slices;
+        self
+    }
+
+    /// Sets the normal of the slicing planes for [`SdfTexture::Contours`].
+    pub fn with_slice_normal(mut self, normal: Vector) -> Self {
+        self.slice_normal = normal.normalize();
+        self
+    }
+
+    /// Sets the number of grid cells across each slice used to trace
+    /// contours.
+    pub fn with_resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution.max(1);
+        self
+    }
+
+    /// Sets how much the function may overestimate the distance to the
+    /// surface. Sphere tracing takes smaller steps for larger values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lipschitz` is not positive.
+    pub fn with_lipschitz(mut self, lipschitz: f64) -> Self {
+        assert!(lipschitz > 0.0, "Lipschitz factor must be positive");
+        self.lipschitz = lipschitz;
+        self
+    }
+
+    /// Returns how much the function may overestimate the distance to the
+    /// surface.
+    pub fn lipschitz(&self) -> f64 {
+        self.lipschitz
+    }
+
+    /// Returns the signed distance at `p`, cut by the bounding box.
+    fn distance(&self, p: Vector) -> f64 {
+        let q = p
+            .sub(self.bx.center())
+            .abs()
+            .sub(self.bx.size().mul_scalar(0.5));
+        let outside = q.max(Vector::default()).length();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        (self.func)(p).max(outside + inside)
+    }
+
+    /// Returns the outward normal at `p`.
+    fn normal(&self, p: Vector) -> Vector {
+        let h = 1e-5;
+        let d = |v: Vector| self.distance(p.add(v)) - self.distance(p.sub(v));
+        Vector::new(
+            d(Vector::new(h, 0.0, 0.0)),
+            d(Vector::new(0.0, h, 0.0)),
+            d(Vector::new(0.0, 0.0, h)),
+        )
+        .normalize()
+    }
+
+    /// Returns the isocontours on the plane `p.dot(normal) = s`, spanned by
+    /// the unit vectors `u` and `w`.
+    fn contours(&self, normal: Vector, u: Vector, w: Vector, s: f64) -> Vec<Vec<Vector>> {
+        // Extent of the bounding box on the plane, padded by a cell so that
+        // contours cut by the box are closed.
+        let corners = self.corners();
+        let range = |axis: Vector| {
+            corners.iter().fold((f64::MAX, f64::MIN), |(lo, hi), c| {
+                (lo.min(c.dot(axis)), hi.max(c.dot(axis)))
+            })
+        };
+        let (u0, u1) = range(u);
+        let (w0, w1) = range(w);
+        let cell = (u1 - u0).max(w1 - w0) / self.resolution as f64;
+        if cell <= 0.0 {
+            return Vec::new();
+        }
+        let nu = ((u1 - u0) / cell).ceil() as usize + 2;
+        let nw = ((w1 - w0) / cell).ceil() as usize + 2;
+        let origin = normal
+            .mul_scalar(s)
+            .add(u.mul_scalar(u0 - cell))
+            .add(w.mul_scalar(w0 - cell));
+        let point = |i: f64, j: f64| {
+            origin
+                .add(u.mul_scalar(i * cell))
+                .add(w.mul_scalar(j * cell))
+        };
+
+        // Locates the surface between two grid positions on either side of it.
+        let crossing = |a: (f64, f64), b: (f64, f64), a_inside: bool| {
+            let (mut lo, mut hi) = (a, b);
+            for _ in 0..CONTOUR_REFINE {
+                let mid = ((lo.0 + hi.0) / 2.0, (lo.1 + hi.1) / 2.0);
+                if (self.distance(point(mid.0, mid.1)) < 0.0) == a_inside {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            point((lo.0 + hi.0) / 2.0, (lo.1 + hi.1) / 2.0)
+        };
+
+        let grid: Vec<Vec<bool>> = (0..=nu)
+            .map(|i| {
+                (0..=nw)
+                    .map(|j| self.distance(point(i as f64, j as f64)) < 0.0)
+                    .collect()
+            })
+            .collect();
+
+        let mut result = Vec::new();
+        for i in 0..nu {
+            for j in 0..nw {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let states: Vec<bool> = corners.iter().map(|&(ci, cj)| grid[ci][cj]).collect();
+                if states.iter().all(|s| *s) || states.iter().all(|s| !*s) {
+                    continue;
+                }
+                // Crossings on the edges whose corners differ, in order
+                // around the cell.
+                let edges: Vec<Vector> = (0..4)
+                    .filter(|&k| states[k] != states[(k + 1) % 4])
+                    .map(|k| {
+                        let (ai, aj) = corners[k];
+                        let (bi, bj) = corners[(k + 1) % 4];
+                        crossing((ai as f64, aj as f64), (bi as f64, bj as f64), states[k])
+                    })
+                    .collect();
+                if edges.len() == 2 {
+                    result.push(vec![edges[0], edges[1]]);
+                } else if edges.len() == 4 {
+                    // Saddle: decide the pairing from the cell center.
+                    let center = self.distance(point(i as f64 + 0.5, j as f64 + 0.5)) < 0.0;
+                    if center == states[0] {
+                        result.push(vec![edges[0], edges[1]]);
+                        result.push(vec![edges[2], edges[3]]);
+                    } else {
+                        result.push(vec![edges[3], edges[0]]);
+                        result.push(vec![edges[1], edges[2]]);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the isocontours on `slices` evenly spaced planes
+    /// perpendicular to `normal`.
+    fn slice_paths(&self, normal: Vector) -> Vec<Vec<Vector>> {
+        let n = normal.normalize();
+        let a = if n.x.abs() > 0.9 {
+            Vector::new(0.0, 1.0, 0.0)
+        } else {
+            Vector::new(1.0, 0.0, 0.0)
+        };
+        let u = a.sub(n.mul_scalar(n.dot(a))).normalize();
+        let w = n.cross(u);
+
+        let (s0, s1) = self
+            .corners()
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), c| {
+                (lo.min(c.dot(n)), hi.max(c.dot(n)))
+            });
+        let mut paths = Vec::new();
+        for k in 0..self.slices {
+            let s = s0 + (s1 - s0) * (k as f64 + 0.5) / self.slices as f64;
+            paths.extend(self.contours(n, u, w, s));
+        }
+        paths
+    }
+
+    /// Returns the corners of the bounding box.
+    fn corners(&self) -> [Vector; 8] {
+        let (a, b) = (self.bx.min, self.bx.max);
+        [
+            Vector::new(a.x, a.y, a.z),
+            Vector::new(b.x, a.y, a.z),
+            Vector::new(a.x, b.y, a.z),
+            Vector::new(b.x, b.y, a.z),
+            Vector::new(a.x, a.y, b.z),
+            Vector::new(b.x, a.y, b.z),
+            Vector::new(a.x, b.y, b.z),
+            Vector::new(b.x, b.y, b.z),
+        ]
+    }
+}
+
+impl<F> Shape for SdfShape<F>
+where
+    F: Fn(Vector) -> f64 + Send + Sync,
+{
+    fn bounding_box(&self) -> Box {
+        self.bx
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        self.distance(v) <= f
+    }
+
+    fn intersect(&self, r: Ray) -> Hit {
+        let (t0, t1) = self.bx.intersect(r);
+        if t1 < t0 || t1 <= 1e-2 {
+            return Hit::no_hit();
+        }
+
+        // Skip past the surface a ray starts on, then march towards the
+        // next sign change of the distance: entering the solid from
+        // outside, or leaving it from inside.
+        let mut t = t0.max(1e-2);
+        let sign = if self.distance(r.position(t)) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        let mut previous = t;
+        for _ in 0..MAX_STEPS {
+            if t > t1 + HIT_EPSILON {
+                break;
+            }
+            let d = sign * self.distance(r.position(t));
+            if d < 0.0 {
+                // Overstepped a distance function that overestimates
+                let (mut lo, mut hi) = (previous, t);
+                for _ in 0..CONTOUR_REFINE {
+                    let mid = (lo + hi) / 2.0;
+                    if sign * self.distance(r.position(mid)) < 0.0 {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+                t = hi;
+                break;
+            }
+            if d < HIT_EPSILON {
+                break;
+            }
+            previous = t;
+            t += d / self.lipschitz;
+        }
+        if t > t1 + HIT_EPSILON || sign * self.distance(r.position(t)) >= 10.0 * HIT_EPSILON {
+            return Hit::no_hit();
+        }
+        let p = r.position(t);
+        Hit::new(t).with_surface(p, self.normal(p))
+    }
+
+    fn paths(&self) -> Paths {
+        let paths = match self.texture {
+            SdfTexture::Contours => self.slice_paths(self.slice_normal),
+            SdfTexture::CrossContours => [
+                Vector::new(1.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ]
+            .into_iter()
+            .flat_map(|n| self.slice_paths(n))
+            .collect(),
+        };
+        Paths::from_vec(paths)
+    }
+}
+
+/// Returns the signed distance function of a sphere.
+pub fn sphere(center: Vector, radius: f64) -> impl Fn(Vector) -> f64 + Send + Sync {
+    move |p| p.distance(center) - radius
+}
+
+/// Returns the signed distance function of an axis-aligned box spanning
+/// `min` to `max`.
+pub fn cuboid(min: Vector, max: Vector) -> impl Fn(Vector) -> f64 + Send + Sync {
+    let center = min.add(max).mul_scalar(0.5);
+    let half = max.sub(min).mul_scalar(0.5);
+    move |p| {
+        let q = p.sub(center).abs().sub(half);
+        q.max(Vector::default()).length() + q.x.max(q.y).max(q.z).min(0.0)
+    }
+}
+
+/// Returns the signed distance function of a torus around the Z axis
+/// through `center`.
+pub fn torus(
+    center: Vector,
+    major_radius: f64,
+    minor_radius: f64,
+) -> impl Fn(Vector) -> f64 + Send + Sync {
+    move |p| {
+        let q = p.sub(center);
+        (q.x.hypot(q.y) - major_radius).hypot(q.z) - minor_radius
+    }
+}
+
+/// Returns the union of two solids.
+pub fn union<A, B>(a: A, b: B) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+    B: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| a(p).min(b(p))
+}
+
+/// Returns the intersection of two solids.
+pub fn intersection<A, B>(a: A, b: B) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+    B: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| a(p).max(b(p))
+}
+
+/// Returns the solid `a` with `b` removed.
+pub fn difference<A, B>(a: A, b: B) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+    B: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| a(p).max(-b(p))
+}
+
+/// Returns the union of two solids with a fillet of size `k` where they
+/// meet.
+///
+/// # Example
+///
+/// ```
+/// use larnt::sdf;
+/// use larnt::Vector;
+///
+/// let a = sdf::sphere(Vector::new(-1.0, 0.0, 0.0), 1.0);
+/// let b = sdf::sphere(Vector::new(1.0, 0.0, 0.0), 1.0);
+/// let melted = sdf::smooth_union(a, b, 0.5);
+///
+/// // The spheres touch at the origin; the fillet fills the gap around it
+/// assert!(melted(Vector::new(0.0, 0.2, 0.0)) < 0.0);
+/// ```
+pub fn smooth_union<A, B>(a: A, b: B, k: f64) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+    B: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| {
+        let (da, db) = (a(p), b(p));
+        if k <= 0.0 {
+            return da.min(db);
+        }
+        let h = (0.5 + 0.5 * (db - da) / k).clamp(0.0, 1.0);
+        db + (da - db) * h - k * h * (1.0 - h)
+    }
+}
+
+/// Returns a blend of two solids, morphing from `a` at `t = 0` to `b` at
+/// `t = 1`.
+pub fn blend<A, B>(a: A, b: B, t: f64) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+    B: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| a(p) * (1.0 - t) + b(p) * t
+}
+
+/// Returns a solid twisted around the Z axis by `k` radians per unit of
+/// height.
+///
+/// Twisting stretches distances, so shapes using it need a
+/// [`lipschitz`](SdfShape::with_lipschitz) factor of about
+/// `sqrt(1 + (k * r)^2)`, where `r` is the largest distance from the axis.
+pub fn twist<A>(a: A, k: f64) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| {
+        let (s, c) = (-k * p.z).sin_cos();
+        a(Vector::new(c * p.x - s * p.y, s * p.x + c * p.y, p.z))
+    }
+}
+
+/// Returns a solid grown by `radius`, rounding its edges and corners.
+pub fn round<A>(a: A, radius: f64) -> impl Fn(Vector) -> f64 + Send + Sync
+where
+    A: Fn(Vector) -> f64 + Send + Sync,
+{
+    move |p| a(p) - radius
+}
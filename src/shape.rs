@@ -41,9 +41,11 @@
 //! ```
 
 use crate::bounding_box::Box;
+use crate::camera::ViewInfo;
 use crate::hit::Hit;
 use crate::matrix::Matrix;
 use crate::path::Paths;
+use crate::plane::Plane;
 use crate::ray::Ray;
 use crate::vector::Vector;
 
@@ -63,6 +65,8 @@ use crate::vector::Vector;
 /// # Optional Methods
 ///
 /// - [`compile`](Shape::compile): Perform any preprocessing (default: no-op)
+/// - [`section`](Shape::section): Outline where a plane cuts the solid
+/// - [`paths_for_view`](Shape::paths_for_view): Paths that depend on the camera
 pub trait Shape {
     /// Performs any preprocessing needed before rendering.
     ///
@@ -98,6 +102,25 @@ pub trait Shape {
     /// this might be the 12 edges. For a sphere, it could be latitude and
     /// longitude lines. Custom implementations can return any pattern.
     fn paths(&self) -> Paths;
+
+    /// Returns the 3D paths of this shape as seen from `view`.
+    ///
+    /// [`Scene`](crate::Scene) renders call this with the actual camera, so
+    /// shapes whose paths depend on the viewpoint, such as silhouettes, stay
+    /// correct when the camera moves. The default returns
+    /// [`paths`](Shape::paths).
+    fn paths_for_view(&self, _view: &ViewInfo) -> Paths {
+        self.paths()
+    }
+
+    /// Returns the outline of the cross-section of this solid with a plane.
+    ///
+    /// This is drawn where a clip plane of a [`Scene`](crate::Scene) cuts the
+    /// shape. The default traces the outline from [`contains`](Shape::contains)
+    /// with [`Plane::section`]; shapes with an exact section can override it.
+    fn section(&self, plane: &Plane) -> Paths {
+        plane.section(self)
+    }
 }
 
 /// A shape that represents empty space.
@@ -146,6 +169,22 @@ impl Shape for EmptyShape {
 /// let transform = Matrix::rotate(Vector::new(0.0, 0.0, 1.0), radians(45.0));
 /// let rotated = TransformedShape::new(cube, transform);
 /// ```
+///
+/// Hits are measured along the world-space ray: `t` is the distance in the
+/// units of the ray passed to [`intersect`](Shape::intersect), even when the
+/// matrix scales the shape, and the normal is mapped back to world space.
+///
+/// ```
+/// use larnt::{Matrix, Ray, Shape, Sphere, TransformedShape, Vector};
+/// use std::sync::Arc;
+///
+/// let sphere = Arc::new(Sphere::new(Vector::default(), 1.0));
+/// let scaled = TransformedShape::new(sphere, Matrix::scale(Vector::new(2.0, 2.0, 2.0)));
+///
+/// let hit = scaled.intersect(Ray::new(Vector::new(-10.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)));
+/// assert!((hit.t - 8.0).abs() < 1e-9);
+/// assert!((hit.normal.x + 1.0).abs() < 1e-9);
+/// ```
 pub struct TransformedShape {
     /// The underlying shape being transformed.
     pub shape: std::sync::Arc<dyn Shape + Send + Sync>,
@@ -186,10 +225,46 @@ impl Shape for TransformedShape {
     }
 
     fn intersect(&self, r: Ray) -> Hit {
-        self.shape.intersect(self.inverse.mul_ray(r))
+        let local = self.inverse.mul_ray(r);
+        let hit = self.shape.intersect(local);
+        if !hit.is_ok() {
+            return hit;
+        }
+        // The local ray is renormalized, so measure `t` again in world space.
+        let position = self.matrix.mul_position(local.position(hit.t));
+        let t = position.sub(r.origin).dot(r.direction) / r.direction.dot(r.direction);
+        let normal = if hit.normal == Vector::default() {
+            hit.normal
+        } else {
+            self.inverse
+                .transpose()
+                .mul_direction(hit.normal)
+                .normalize()
+        };
+        Hit {
+            t,
+            position,
+            normal,
+            ..hit
+        }
     }
 
     fn paths(&self) -> Paths {
         self.shape.paths().transform(&self.matrix)
     }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.shape
+            .paths_for_view(&view.transform(&self.inverse))
+            .transform(&self.matrix)
+    }
+
+    fn section(&self, plane: &Plane) -> Paths {
+        let local = Plane::new(
+            self.inverse.mul_position(plane.point),
+            self.matrix.transpose().mul_direction(plane.normal),
+        )
+        .with_section_resolution(plane.section_resolution);
+        self.shape.section(&local).transform(&self.matrix)
+    }
 }
@@ -22,6 +22,7 @@
 //! ```
 
 use crate::bounding_box::Box;
+use crate::camera::{Camera, ViewInfo};
 use crate::hit::Hit;
 use crate::matrix::Matrix;
 use crate::path::Paths;
@@ -93,6 +94,11 @@ impl Sphere {
         self.texture = texture;
         self
     }
+
+    fn hit(&self, r: Ray, t: f64) -> Hit {
+        let p = r.position(t);
+        Hit::new(t).with_surface(p, p.sub(self.center).normalize())
+    }
 }
 
 impl Shape for Sphere {
@@ -115,11 +121,11 @@ impl Shape for Sphere {
             let d = d.sqrt();
             let t1 = -b - d;
             if t1 > 1e-2 {
-                return Hit::new(t1);
+                return self.hit(r, t1);
             }
             let t2 = -b + d;
             if t2 > 1e-2 {
-                return Hit::new(t2);
+                return self.hit(r, t2);
             }
         }
         Hit::no_hit()
@@ -296,6 +302,10 @@ pub fn lat_lng_to_xyz(lat: f64, lng: f64, radius: f64) -> Vector {
 /// draws only the visible outline of the sphere as seen from the camera.
 /// This is useful for cleaner, more stylized renderings.
 ///
+/// When rendered by a [`Scene`](crate::Scene), the outline follows the
+/// render camera (see [`Shape::paths_for_view`]). The stored `eye` and `up`
+/// are only used by [`Shape::paths`].
+///
 /// # Example
 ///
 /// ```
@@ -332,6 +342,11 @@ impl OutlineSphere {
             up,
         }
     }
+
+    /// Creates a new outline sphere using the eye and up vectors of a [`Camera`].
+    pub fn from_camera(camera: &Camera, center: Vector, radius: f64) -> Self {
+        OutlineSphere::new(camera.eye, camera.up, center, radius)
+    }
 }
 
 impl Shape for OutlineSphere {
@@ -348,20 +363,31 @@ impl Shape for OutlineSphere {
     }
 
     fn paths(&self) -> Paths {
+        self.outline(self.eye, self.up)
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.outline(view.eye, view.up)
+    }
+}
+
+impl OutlineSphere {
+    /// Returns the outline as seen from `eye`.
+    fn outline(&self, eye: Vector, up: Vector) -> Paths {
         let center = self.sphere.center;
         let radius = self.sphere.radius;
 
-        let hyp = center.sub(self.eye).length();
+        let hyp = center.sub(eye).length();
         let opp = radius;
         let theta = (opp / hyp).asin();
         let adj = opp / theta.tan();
         let d = theta.cos() * adj;
         let r = theta.sin() * adj;
 
-        let w = center.sub(self.eye).normalize();
+        let w = center.sub(eye).normalize();
 
         // Handle case when w is parallel to up vector by finding a perpendicular vector
-        let cross = w.cross(self.up);
+        let cross = w.cross(up);
         let u = if cross.length_squared() < 1e-18 {
             // w is parallel to up, use the minimum axis approach to find a perpendicular
             w.cross(w.min_axis()).normalize()
@@ -369,7 +395,7 @@ impl Shape for OutlineSphere {
             cross.normalize()
         };
         let v = w.cross(u).normalize();
-        let c = self.eye.add(w.mul_scalar(d));
+        let c = eye.add(w.mul_scalar(d));
 
         let mut path = Vec::new();
         for i in 0..=360 {
@@ -0,0 +1,105 @@
+//! Stroke styles.
+//!
+//! This module provides the [`Style`] struct, which describes how the paths
+//! of a shape are drawn. A style is attached to a shape when it is added to a
+//! [`Scene`](crate::Scene) and is carried through rendering to the SVG output,
+//! where paths sharing a style are written as one `<g>` group.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use larnt::{Cube, Scene, Sphere, Style, Vector};
+//!
+//! let mut scene = Scene::new();
+//! scene.add(Cube::new(Vector::new(-1.0, -1.0, -1.0), Vector::new(1.0, 1.0, 1.0)));
+//! scene.add_styled(
+//!     Sphere::new(Vector::new(0.0, 0.0, 1.5), 0.5),
+//!     Style::new().with_color("red").with_width(0.5).with_layer("spheres"),
+//! );
+//!
+//! let paths = scene.render(
+//!     Vector::new(4.0, 3.0, 2.0),
+//!     Vector::new(0.0, 0.0, 0.0),
+//!     Vector::new(0.0, 0.0, 1.0),
+//!     1024.0, 1024.0, 50.0, 0.1, 10.0, 0.01,
+//! );
+//! paths.write_to_svg("output.svg", 1024.0, 1024.0).unwrap();
+//! ```
+
+/// The stroke style of a set of paths.
+///
+/// The default style is a solid black line of width 1 with no layer, which
+/// matches the output for unstyled paths.
+///
+/// # Example
+///
+/// ```
+/// use larnt::Style;
+///
+/// let hidden = Style::new()
+///     .with_color("#808080")
+///     .with_dash(vec![4.0, 2.0]);
+/// assert_eq!(hidden.width, 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// The stroke colour, in any form SVG accepts (e.g. `"red"` or `"#ff0000"`).
+    pub color: String,
+    /// The stroke width in output units.
+    pub width: f64,
+    /// The stroke opacity, from `0.0` to `1.0`.
+    pub opacity: f64,
+    /// The dash pattern as alternating dash and gap lengths. Empty for a solid line.
+    pub dash: Vec<f64>,
+    /// The layer the paths belong to, if any.
+    pub layer: Option<String>,
+}
+
+impl Style {
+    /// Creates the default style.
+    pub fn new() -> Self {
+        Style {
+            color: "black".to_string(),
+            width: 1.0,
+            opacity: 1.0,
+            dash: Vec::new(),
+            layer: None,
+        }
+    }
+
+    /// Sets the stroke colour.
+    pub fn with_color(mut self, color: &str) -> Self {
+        self.color = color.to_string();
+        self
+    }
+
+    /// Sets the stroke width.
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the stroke opacity.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Sets the dash pattern.
+    pub fn with_dash(mut self, dash: Vec<f64>) -> Self {
+        self.dash = dash;
+        self
+    }
+
+    /// Sets the layer name.
+    pub fn with_layer(mut self, layer: &str) -> Self {
+        self.layer = Some(layer.to_string());
+        self
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::new()
+    }
+}
@@ -0,0 +1,500 @@
+//! Torus primitive.
+//!
+//! This module provides the [`Torus`] shape (centered at the origin, around
+//! the Z axis) with multiple texture options, and [`OutlineTorus`] which
+//! renders as a silhouette from the camera's perspective.
+//!
+//! # Example
+//!
+//! ```
+//! use larnt::{Scene, Torus, TorusTexture, Vector};
+//!
+//! // A ring with a major radius of 2.0 and a tube radius of 0.5
+//! let torus = Torus::new(2.0, 0.5);
+//!
+//! // Or wound with a spiral
+//! let spiral = Torus::new(2.0, 0.5).with_texture(TorusTexture::Spiral(24));
+//!
+//! let mut scene = Scene::new();
+//! scene.add(torus);
+//! ```
+
+use crate::bounding_box::Box;
+use crate::camera::{Camera, ViewInfo};
+use crate::hit::Hit;
+use crate::matrix::Matrix;
+use crate::path::Paths;
+use crate::ray::Ray;
+use crate::shape::{Shape, TransformedShape};
+use crate::util::radians;
+use crate::vector::Vector;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Texture style for Torus shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TorusTexture {
+    /// Meridian and parallel rings (default)
+    #[default]
+    Rings,
+    /// Meridian rings around the tube only
+    Meridians,
+    /// Parallel rings around the axis only
+    Parallels,
+    /// A single line winding around the tube the given number of times
+    Spiral(u32),
+    /// Random small loops on the surface
+    RandomLoops(u64),
+}
+
+/// A torus centered at the origin, around the Z axis.
+///
+/// The torus is defined by its major radius, the distance from the axis to
+/// the center of the tube, and its minor radius, the radius of the tube.
+/// The default paths are meridian and parallel rings. You can use
+/// [`with_texture`](Torus::with_texture) to select different texture styles.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{Ray, Shape, Torus, Vector};
+///
+/// let torus = Torus::new(2.0, 0.5);
+/// let hit = torus.intersect(Ray::new(Vector::new(5.0, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0)));
+/// assert!((hit.t - 2.5).abs() < 1e-9);
+/// assert!(torus.contains(Vector::new(0.0, 2.0, 0.0), 0.0));
+/// assert!(!torus.contains(Vector::new(0.0, 0.0, 0.0), 0.0));
+///
+/// // Small tori are solved at unit scale, so they are hit just as reliably.
+/// let small = Torus::new(0.1, 0.025);
+/// let hit = small.intersect(Ray::new(Vector::new(0.0, -0.1, 0.3), Vector::new(0.0, 0.0, -1.0)));
+/// assert!((hit.t - 0.275).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Torus {
+    /// The distance from the Z axis to the center of the tube.
+    pub major_radius: f64,
+    /// The radius of the tube.
+    pub minor_radius: f64,
+    /// The texture style for the torus.
+    pub texture: TorusTexture,
+}
+
+impl Torus {
+    /// Creates a new torus with the given major and minor radius.
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Torus {
+            major_radius,
+            minor_radius,
+            texture: TorusTexture::default(),
+        }
+    }
+
+    /// Sets the texture style for the torus.
+    pub fn with_texture(mut self, texture: TorusTexture) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Returns the point at angle `u` around the axis and `v` around the tube.
+    fn point(&self, u: f64, v: f64) -> Vector {
+        let d = self.major_radius + self.minor_radius * v.cos();
+        Vector::new(d * u.cos(), d * u.sin(), self.minor_radius * v.sin())
+    }
+
+    fn hit(&self, r: Ray, t: f64) -> Hit {
+        let p = r.position(t);
+        let c = Vector::new(p.x, p.y, 0.0)
+            .normalize()
+            .mul_scalar(self.major_radius);
+        Hit::new(t).with_surface(p, p.sub(c).normalize())
+    }
+}
+
+impl Shape for Torus {
+    fn bounding_box(&self) -> Box {
+        let a = self.major_radius + self.minor_radius;
+        let b = self.minor_radius;
+        Box::new(Vector::new(-a, -a, -b), Vector::new(a, a, b))
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        let q = (v.x * v.x + v.y * v.y).sqrt() - self.major_radius;
+        let r = self.minor_radius + f;
+        r >= 0.0 && q * q + v.z * v.z <= r * r
+    }
+
+    fn intersect(&self, ray: Ray) -> Hit {
+        // Solve in a unit-speed frame whose origin is the point on the ray
+        // closest to the center, scaled so that the larger radius is one.
+        // This keeps the quartic well conditioned at any size.
+        let len = ray.direction.length();
+        let scale = self.major_radius.max(self.minor_radius);
+        if len == 0.0 || scale <= 0.0 {
+            return Hit::no_hit();
+        }
+        let d = ray.direction.div_scalar(len);
+        let s0 = -ray.origin.dot(d);
+        let o = ray.origin.add(d.mul_scalar(s0)).div_scalar(scale);
+
+        let a2 = (self.major_radius / scale).powi(2);
+        let b2 = (self.minor_radius / scale).powi(2);
+        let e = o.dot(o) - a2 - b2;
+        let f = o.dot(d);
+        let coefficients = [
+            e * e - 4.0 * a2 * (b2 - o.z * o.z),
+            4.0 * f * e + 8.0 * a2 * o.z * d.z,
+            2.0 * e + 4.0 * f * f + 4.0 * a2 * d.z * d.z,
+            4.0 * f,
+        ];
+
+        let mut best = f64::INFINITY;
+        for s in solve_quartic(coefficients) {
+            let s = polish_quartic(coefficients, s);
+            let t = (s * scale + s0) / len;
+            if t > 1e-2 && t < best {
+                best = t;
+            }
+        }
+        if best.is_finite() {
+            self.hit(ray, best)
+        } else {
+            Hit::no_hit()
+        }
+    }
+
+    fn paths(&self) -> Paths {
+        match self.texture {
+            TorusTexture::Rings => {
+                let mut paths = self.paths_meridians();
+                paths.extend(self.paths_parallels());
+                paths
+            }
+            TorusTexture::Meridians => self.paths_meridians(),
+            TorusTexture::Parallels => self.paths_parallels(),
+            TorusTexture::Spiral(turns) => self.paths_spiral(turns),
+            TorusTexture::RandomLoops(seed) => self.paths_random_loops(seed),
+        }
+    }
+}
+
+impl Torus {
+    /// Meridian rings around the tube
+    fn paths_meridians(&self) -> Paths {
+        let mut paths = Vec::new();
+        for u in (0..360).step_by(10) {
+            let u = radians(u as f64);
+            let path = (0..=360)
+                .map(|v| self.point(u, radians(v as f64)))
+                .collect();
+            paths.push(path);
+        }
+        Paths::from_vec(paths)
+    }
+
+    /// Parallel rings around the axis
+    fn paths_parallels(&self) -> Paths {
+        let mut paths = Vec::new();
+        for v in (0..360).step_by(30) {
+            let v = radians(v as f64);
+            let path = (0..=360)
+                .map(|u| self.point(radians(u as f64), v))
+                .collect();
+            paths.push(path);
+        }
+        Paths::from_vec(paths)
+    }
+
+    /// A single line winding around the tube
+    fn paths_spiral(&self, turns: u32) -> Paths {
+        let turns = turns.max(1) as f64;
+        let n = (turns * 360.0) as usize;
+        let path = (0..=n)
+            .map(|i| {
+                let t = i as f64 / n as f64;
+                self.point(2.0 * PI * t, 2.0 * PI * turns * t)
+            })
+            .collect();
+        Paths::from_vec(vec![path])
+    }
+
+    /// Random small loops on the surface
+    fn paths_random_loops(&self, seed: u64) -> Paths {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut paths = Vec::new();
+        for _ in 0..200 {
+            let u0 = rng.gen::<f64>() * 2.0 * PI;
+            let v0 = rng.gen::<f64>() * 2.0 * PI;
+            let m = self.minor_radius * (rng.gen::<f64>() * 0.3 + 0.05);
+            // Scale the loop by the local metric so it stays round on the surface
+            let du = m / (self.major_radius + self.minor_radius * v0.cos());
+            let dv = m / self.minor_radius;
+            let path = (0..=72)
+                .map(|i| {
+                    let a = radians(i as f64 * 5.0);
+                    self.point(u0 + du * a.cos(), v0 + dv * a.sin())
+                })
+                .collect();
+            paths.push(path);
+        }
+        Paths::from_vec(paths)
+    }
+}
+
+/// A torus that renders as a silhouette from the camera's perspective.
+///
+/// Unlike [`Torus`] which draws a texture, `OutlineTorus` draws only the
+/// contour of the torus as seen from the camera: the outer silhouette and
+/// the rim of the hole.
+///
+/// When rendered by a [`Scene`](crate::Scene), the outline follows the
+/// render camera (see [`Shape::paths_for_view`]). The stored `eye` and `up`
+/// are only used by [`Shape::paths`].
+///
+/// Where the eye is inside the tube there is no contour, and the outline is
+/// split into separate paths on either side of the gap.
+///
+/// # Example
+///
+/// ```
+/// use larnt::{OutlineTorus, Shape, Vector};
+///
+/// // An eye inside the tube, looking along it.
+/// let outline = OutlineTorus::new(Vector::new(2.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0), 2.0, 0.5);
+/// for path in &outline.paths().paths {
+///     for w in path.windows(2) {
+///         assert!(w[0].distance(w[1]) < 0.1);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OutlineTorus {
+    /// The underlying torus geometry.
+    pub torus: Torus,
+    /// The camera position.
+    pub eye: Vector,
+    /// The up direction.
+    pub up: Vector,
+}
+
+impl OutlineTorus {
+    /// Creates a new outline torus.
+    pub fn new(eye: Vector, up: Vector, major_radius: f64, minor_radius: f64) -> Self {
+        OutlineTorus {
+            torus: Torus::new(major_radius, minor_radius),
+            eye,
+            up,
+        }
+    }
+
+    /// Creates a new outline torus using the eye and up vectors of a [`Camera`].
+    pub fn from_camera(camera: &Camera, major_radius: f64, minor_radius: f64) -> Self {
+        OutlineTorus::new(camera.eye, camera.up, major_radius, minor_radius)
+    }
+}
+
+impl Shape for OutlineTorus {
+    fn bounding_box(&self) -> Box {
+        self.torus.bounding_box()
+    }
+
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        self.torus.contains(v, f)
+    }
+
+    fn intersect(&self, r: Ray) -> Hit {
+        self.torus.intersect(r)
+    }
+
+    fn paths(&self) -> Paths {
+        self.outline(self.eye)
+    }
+
+    fn paths_for_view(&self, view: &ViewInfo) -> Paths {
+        self.outline(view.eye)
+    }
+}
+
+impl OutlineTorus {
+    /// Returns the outline as seen from `eye`.
+    fn outline(&self, eye: Vector) -> Paths {
+        // At angle u around the axis the tube is a circle of radius r around
+        // c(u), with normals n(v) = cos(v) * (cos(u), sin(u), 0) + sin(v) * Z.
+        // The point c + r * n is on the contour when n.(eye - c) = r, i.e.
+        // a * cos(v) + b * sin(v) = r with a and b the components of eye - c.
+        let r = self.torus.minor_radius;
+        // Push the contour off the surface by the sag of its one-degree
+        // chords, so that the grazing view ray does not hit the torus itself.
+        let nudge = 2.0 * (self.torus.major_radius + r) * (1.0 - (PI / 360.0).cos());
+        let mut paths = Vec::new();
+        let mut p0 = Vec::new();
+        let mut p1 = Vec::new();
+        for u in 0..=360 {
+            let u = radians(u as f64);
+            let radial = Vector::new(u.cos(), u.sin(), 0.0);
+            let w = eye.sub(radial.mul_scalar(self.torus.major_radius));
+            let a = w.dot(radial);
+            let b = w.z;
+            let ratio = r / (a * a + b * b).sqrt();
+            if ratio > 1.0 {
+                // Eye is inside the tube - no proper contour, so end the
+                // current pieces here.
+                for path in [&mut p0, &mut p1] {
+                    if path.len() > 1 {
+                        paths.push(std::mem::take(path));
+                    }
+                    path.clear();
+                }
+                continue;
+            }
+            let phi = b.atan2(a);
+            let offset = ratio.acos();
+            for (path, v) in [(&mut p0, phi + offset), (&mut p1, phi - offset)] {
+                let n = radial
+                    .mul_scalar(v.cos())
+                    .add(Vector::new(0.0, 0.0, v.sin()));
+                let c = radial.mul_scalar(self.torus.major_radius);
+                path.push(c.add(n.mul_scalar(r + nudge)));
+            }
+        }
+        paths.extend([p0, p1].into_iter().filter(|p| p.len() > 1));
+        Paths::from_vec(paths)
+    }
+}
+
+/// Creates a torus centered at `center` whose axis points along `axis`.
+///
+/// # Arguments
+///
+/// * `up` - Up direction vector
+/// * `center` - Center of the torus
+/// * `axis` - Direction of the torus axis
+/// * `major_radius` - Distance from the axis to the center of the tube
+/// * `minor_radius` - Radius of the tube
+pub fn new_transformed_torus(
+    up: Vector,
+    center: Vector,
+    axis: Vector,
+    major_radius: f64,
+    minor_radius: f64,
+) -> TransformedShape {
+    let u = axis.cross(up);
+    let m = if u.length_squared() > 0.0 {
+        let a = axis.normalize().dot(up).acos();
+        Matrix::rotate(u.normalize(), a).translated(center)
+    } else {
+        Matrix::translate(center)
+    };
+    let t = Torus::new(major_radius, minor_radius);
+    TransformedShape::new(Arc::new(t), m)
+}
+
+/// Returns the real roots of `x^4 + c[3] x^3 + c[2] x^2 + c[1] x + c[0]`.
+///
+/// Uses Ferrari's method: the depressed quartic is factored into two
+/// quadratics with the help of a root of its resolvent cubic.
+fn solve_quartic(c: [f64; 4]) -> Vec<f64> {
+    let [c0, c1, c2, c3] = c;
+    // Substitute x = y - c3 / 4 to eliminate the cubic term
+    let sq = c3 * c3;
+    let p = -3.0 / 8.0 * sq + c2;
+    let q = 1.0 / 8.0 * sq * c3 - 1.0 / 2.0 * c3 * c2 + c1;
+    let r = -3.0 / 256.0 * sq * sq + 1.0 / 16.0 * sq * c2 - 1.0 / 4.0 * c3 * c1 + c0;
+
+    let mut roots = Vec::new();
+    if r.abs() < 1e-12 {
+        // No absolute term: y (y^3 + p y + q) = 0
+        roots.push(0.0);
+        roots.extend(solve_cubic([q, p, 0.0]));
+    } else {
+        // Take one root of the resolvent cubic to build the two quadratics
+        let z = solve_cubic([1.0 / 2.0 * r * p - 1.0 / 8.0 * q * q, -r, -1.0 / 2.0 * p])
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let mut u = z * z - r;
+        let mut v = 2.0 * z - p;
+        if u.abs() < 1e-12 {
+            u = 0.0;
+        } else if u > 0.0 {
+            u = u.sqrt();
+        } else {
+            return Vec::new();
+        }
+        if v.abs() < 1e-12 {
+            v = 0.0;
+        } else if v > 0.0 {
+            v = v.sqrt();
+        } else {
+            return Vec::new();
+        }
+        let v = if q < 0.0 { -v } else { v };
+        roots.extend(solve_quadratic(v, z - u));
+        roots.extend(solve_quadratic(-v, z + u));
+    }
+    roots.into_iter().map(|y| y - c3 / 4.0).collect()
+}
+
+/// Returns the real roots of `x^3 + c[2] x^2 + c[1] x + c[0]`.
+fn solve_cubic(c: [f64; 3]) -> Vec<f64> {
+    let [c0, c1, c2] = c;
+    // Substitute x = y - c2 / 3 to eliminate the quadratic term
+    let sq = c2 * c2;
+    let p = 1.0 / 3.0 * (-1.0 / 3.0 * sq + c1);
+    let q = 1.0 / 2.0 * (2.0 / 27.0 * c2 * sq - 1.0 / 3.0 * c2 * c1 + c0);
+    let p3 = p * p * p;
+    let d = q * q + p3;
+
+    let roots = if d.abs() < 1e-12 {
+        if q.abs() < 1e-12 {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if d < 0.0 {
+        // Three real roots
+        let phi = 1.0 / 3.0 * (-q / (-p3).sqrt()).clamp(-1.0, 1.0).acos();
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        // One real root
+        let d = d.sqrt();
+        vec![(d - q).cbrt() - (d + q).cbrt()]
+    };
+    roots.into_iter().map(|y| y - c2 / 3.0).collect()
+}
+
+/// Returns the real roots of `x^2 + p x + q`.
+fn solve_quadratic(p: f64, q: f64) -> Vec<f64> {
+    let d = p * p / 4.0 - q;
+    if d.abs() < 1e-12 {
+        vec![-p / 2.0]
+    } else if d < 0.0 {
+        Vec::new()
+    } else {
+        let d = d.sqrt();
+        vec![-p / 2.0 - d, -p / 2.0 + d]
+    }
+}
+
+/// Refines a root of the monic quartic with a few Newton steps.
+fn polish_quartic(c: [f64; 4], mut x: f64) -> f64 {
+    for _ in 0..4 {
+        let f = (((x + c[3]) * x + c[2]) * x + c[1]) * x + c[0];
+        let df = ((4.0 * x + 3.0 * c[3]) * x + 2.0 * c[2]) * x + c[1];
+        if df == 0.0 {
+            break;
+        }
+        let step = f / df;
+        if !step.is_finite() {
+            break;
+        }
+        x -= step;
+    }
+    x
+}
@@ -1,30 +1,89 @@
 use crate::axis::Axis;
 use crate::bounding_box::Box;
+use crate::common::INF;
 use crate::hit::Hit;
 use crate::ray::Ray;
 use crate::shape::Shape;
 use crate::util::median;
+use crate::vector::Vector;
 use std::sync::Arc;
 
 pub struct Tree {
     pub bx: Box,
     pub root: Node,
+    /// Shapes whose bounding box reaches infinity, such as infinite planes,
+    /// and their indices. They are kept out of the hierarchy, whose splits
+    /// and box tests need finite bounds, and are tested on every query.
+    pub unbounded: Vec<(Arc<dyn Shape + Send + Sync>, usize)>,
 }
 
 impl Tree {
     pub fn new(shapes: Vec<Arc<dyn Shape + Send + Sync>>) -> Self {
-        let bx = box_for_arc_shapes(&shapes);
-        let mut root = Node::new(shapes);
+        let mut bounded = Vec::new();
+        let mut ids = Vec::new();
+        let mut unbounded = Vec::new();
+        for (id, shape) in shapes.into_iter().enumerate() {
+            let bx = shape.bounding_box();
+            if [bx.min, bx.max]
+                .iter()
+                .all(|v| v.x.abs() < INF && v.y.abs() < INF && v.z.abs() < INF)
+            {
+                bounded.push(shape);
+                ids.push(id);
+            } else {
+                unbounded.push((shape, id));
+            }
+        }
+        let bx = box_for_arc_shapes(&bounded);
+        let mut root = Node::with_ids(bounded, ids);
         root.split(0);
-        Tree { bx, root }
+        Tree {
+            bx,
+            root,
+            unbounded,
+        }
     }
 
+    /// Returns the closest hit of `r` on the shapes in the tree, with its
+    /// [`position`](Hit::position) and [`shape`](Hit::shape) set.
     pub fn intersect(&self, r: Ray) -> Hit {
         let (tmin, tmax) = self.bx.intersect(r);
-        if tmax < tmin || tmax <= 0.0 {
-            return Hit::no_hit();
+        let mut hit = if tmax < tmin || tmax <= 0.0 || self.root.is_empty() {
+            Hit::no_hit()
+        } else {
+            self.root.intersect(r, tmin, tmax)
+        };
+        for (shape, id) in &self.unbounded {
+            let h = shape.intersect(r);
+            if h.t < hit.t {
+                hit = h.with_shape(*id);
+            }
+        }
+        if !hit.is_ok() {
+            return hit;
+        }
+        Hit {
+            position: r.position(hit.t),
+            ..hit
         }
-        self.root.intersect(r, tmin, tmax)
+    }
+
+    /// Tests if any shape in the tree contains `v` with fuzz factor `f`.
+    ///
+    /// Only shapes whose bounding box, grown by `f`, holds `v` are tested.
+    pub fn contains(&self, v: Vector, f: f64) -> bool {
+        self.root.shape_containing(v, f).is_some()
+    }
+
+    /// Returns the index of a shape that contains `v` with fuzz factor `f`,
+    /// in the list the tree was built from.
+    pub fn shape_containing(&self, v: Vector, f: f64) -> Option<usize> {
+        self.root.shape_containing(v, f).or_else(|| {
+            self.unbounded
+                .iter()
+                .find(|(shape, _)| shape.contains(v, f))
+                .map(|(_, id)| *id)
+        })
     }
 }
 
@@ -43,16 +102,24 @@ pub struct Node {
     pub axis: Axis,
     pub point: f64,
     pub shapes: Vec<Arc<dyn Shape + Send + Sync>>,
+    /// The index of each shape in the list the tree was built from.
+    pub ids: Vec<usize>,
     pub left: Option<std::boxed::Box<Node>>,
     pub right: Option<std::boxed::Box<Node>>,
 }
 
 impl Node {
     pub fn new(shapes: Vec<Arc<dyn Shape + Send + Sync>>) -> Self {
+        let ids = (0..shapes.len()).collect();
+        Node::with_ids(shapes, ids)
+    }
+
+    fn with_ids(shapes: Vec<Arc<dyn Shape + Send + Sync>>, ids: Vec<usize>) -> Self {
         Node {
             axis: Axis::None,
             point: 0.0,
             shapes,
+            ids,
             left: None,
             right: None,
         }
@@ -122,12 +189,58 @@ impl Node {
         }
     }
 
+    /// Tests if the node holds no shapes.
+    fn is_empty(&self) -> bool {
+        self.axis == Axis::None && self.shapes.is_empty()
+    }
+
+    fn shape_containing(&self, v: Vector, f: f64) -> Option<usize> {
+        let m = f.abs();
+        let c = match self.axis {
+            Axis::None => {
+                return self
+                    .shapes
+                    .iter()
+                    .zip(&self.ids)
+                    .find(|(shape, _)| {
+                        let bx = shape.bounding_box();
+                        Box::new(bx.min.sub_scalar(m), bx.max.add_scalar(m)).contains(v)
+                            && shape.contains(v, f)
+                    })
+                    .map(|(_, id)| *id);
+            }
+            Axis::X => v.x,
+            Axis::Y => v.y,
+            Axis::Z => v.z,
+        };
+        let left = self
+            .left
+            .as_ref()
+            .expect("left child must exist when axis is set");
+        let right = self
+            .right
+            .as_ref()
+            .expect("right child must exist when axis is set");
+        let left = if c - m <= self.point {
+            left.shape_containing(v, f)
+        } else {
+            None
+        };
+        left.or_else(|| {
+            if c + m >= self.point {
+                right.shape_containing(v, f)
+            } else {
+                None
+            }
+        })
+    }
+
     fn intersect_shapes(&self, r: Ray) -> Hit {
         let mut hit = Hit::no_hit();
-        for shape in &self.shapes {
+        for (shape, id) in self.shapes.iter().zip(&self.ids) {
             let h = shape.intersect(r);
             if h.t < hit.t {
-                hit = h;
+                hit = h.with_shape(*id);
             }
         }
         hit
@@ -149,29 +262,25 @@ impl Node {
         left.max(right)
     }
 
-    fn partition(
-        &self,
-        axis: Axis,
-        point: f64,
-    ) -> (
-        Vec<Arc<dyn Shape + Send + Sync>>,
-        Vec<Arc<dyn Shape + Send + Sync>>,
-    ) {
-        let mut left = Vec::new();
-        let mut right = Vec::new();
-        for shape in &self.shapes {
+    fn partition(&self, axis: Axis, point: f64) -> (Node, Node) {
+        let mut left = Node::with_ids(Vec::new(), Vec::new());
+        let mut right = Node::with_ids(Vec::new(), Vec::new());
+        for (shape, id) in self.shapes.iter().zip(&self.ids) {
             let bx = shape.bounding_box();
             let (l, r) = bx.partition(axis, point);
             if l {
-                left.push(Arc::clone(shape));
+                left.shapes.push(Arc::clone(shape));
+                left.ids.push(*id);
             }
             if r {
-                right.push(Arc::clone(shape));
+                right.shapes.push(Arc::clone(shape));
+                right.ids.push(*id);
             }
         }
         (left, right)
     }
 
+    #[allow(clippy::only_used_in_recursion)]
     pub fn split(&mut self, depth: usize) {
         if self.shapes.len() < 8 {
             return;
@@ -227,17 +336,16 @@ impl Node {
             return;
         }
 
-        let (l, r) = self.partition(best_axis, best_point);
+        let (mut left, mut right) = self.partition(best_axis, best_point);
         self.axis = best_axis;
         self.point = best_point;
 
-        let mut left = Node::new(l);
-        let mut right = Node::new(r);
         left.split(depth + 1);
         right.split(depth + 1);
 
         self.left = Some(std::boxed::Box::new(left));
         self.right = Some(std::boxed::Box::new(right));
         self.shapes.clear();
+        self.ids.clear();
     }
 }
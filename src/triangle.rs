@@ -31,6 +31,38 @@ impl Triangle {
         let max = self.v1.max(self.v2).max(self.v3);
         self.bx = Box::new(min, max);
     }
+
+    /// Returns the unit normal, oriented by the winding order v1, v2, v3.
+    pub fn normal(&self) -> Vector {
+        let e1 = self.v2.sub(self.v1);
+        let e2 = self.v3.sub(self.v1);
+        e1.cross(e2).normalize()
+    }
+
+    /// Returns the centroid of the triangle, the mean of its vertices.
+    pub fn centroid(&self) -> Vector {
+        self.v1.add(self.v2).add(self.v3).div_scalar(3.0)
+    }
+
+    /// Returns the distance from `v` to the closest point of the triangle.
+    pub fn distance(&self, v: Vector) -> f64 {
+        let e1 = self.v2.sub(self.v1);
+        let e2 = self.v3.sub(self.v1);
+        let n = e1.cross(e2);
+        if n.length() > 0.0 {
+            // Barycentric coordinates of the projection onto the plane.
+            let p = v.sub(self.v1);
+            let nn = n.dot(n);
+            let b2 = p.cross(e2).dot(n) / nn;
+            let b3 = e1.cross(p).dot(n) / nn;
+            if b2 >= 0.0 && b3 >= 0.0 && b2 + b3 <= 1.0 {
+                return p.dot(n).abs() / n.length();
+            }
+        }
+        v.segment_distance(self.v1, self.v2)
+            .min(v.segment_distance(self.v2, self.v3))
+            .min(v.segment_distance(self.v3, self.v1))
+    }
 }
 
 impl Shape for Triangle {
@@ -38,8 +70,9 @@ impl Shape for Triangle {
         self.bx
     }
 
-    fn contains(&self, _v: Vector, _f: f64) -> bool {
-        false
+    /// A triangle has no volume, so only points within `f` of it are inside.
+    fn contains(&self, v: Vector, f: f64) -> bool {
+        f > 0.0 && self.distance(v) <= f
     }
 
     fn intersect(&self, r: Ray) -> Hit {
@@ -64,7 +97,7 @@ impl Shape for Triangle {
         let tz = r.origin.z - self.v1.z;
         let u = (tx * px + ty * py + tz * pz) * inv;
 
-        if u < 0.0 || u > 1.0 {
+        if !(0.0..=1.0).contains(&u) {
             return Hit::no_hit();
         }
 
@@ -83,7 +116,7 @@ impl Shape for Triangle {
             return Hit::no_hit();
         }
 
-        Hit::new(d)
+        Hit::new(d).with_surface(r.position(d), self.normal())
     }
 
     fn paths(&self) -> Paths {
@@ -211,6 +211,15 @@ impl Vector {
         }
     }
 
+    /// Returns the vector with the absolute value of each component.
+    pub fn abs(&self) -> Vector {
+        Vector {
+            x: self.x.abs(),
+            y: self.y.abs(),
+            z: self.z.abs(),
+        }
+    }
+
     pub fn min_axis(&self) -> Vector {
         let x = self.x.abs();
         let y = self.y.abs();